@@ -0,0 +1,33 @@
+// Compare the scalar and SSE2 paths with criterion baselines:
+//   cargo bench --bench math -- --save-baseline scalar
+//   cargo bench --bench math --features simd -- --baseline scalar
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use kengine::math::*;
+
+fn transforms(c: &mut Criterion) {
+    let a = Transform4D::from_mat_vec(Mat3::rot(0.7, Vec3::new(1.0, 2.0, 3.0).normalize()), Vec3::new(1.0, 2.0, 3.0));
+    let b = Transform4D::from_mat_vec(Mat3::rot_y(0.3) * Mat3::scale(2.0, 1.0, 0.5), Vec3::new(-4.0, 0.5, 2.0));
+    let m = a.mat3();
+    let n = b.mat3();
+    let v = Vec3::new(0.5, -1.5, 2.5);
+    let h = Vec4::new(0.5, -1.5, 2.5, 1.0);
+
+    c.bench_function("mat3 * vec3", |bench| bench.iter(|| black_box(m) * black_box(v)));
+    c.bench_function("mat3 * mat3", |bench| bench.iter(|| black_box(m) * black_box(n)));
+    c.bench_function("transform4d * vec3", |bench| bench.iter(|| black_box(a) * black_box(v)));
+    c.bench_function("transform4d * vec4", |bench| bench.iter(|| black_box(a) * black_box(h)));
+    c.bench_function("transform4d * transform4d", |bench| bench.iter(|| black_box(a) * black_box(b)));
+    c.bench_function("vec4 + vec4", |bench| bench.iter(|| black_box(h) + black_box(h)));
+}
+
+fn batches(c: &mut Criterion) {
+    let t = Transform4D::from_mat_vec(Mat3::rot(0.7, Vec3::new(1.0, 2.0, 3.0).normalize()), Vec3::new(1.0, 2.0, 3.0));
+    let points: Vec<Vec3> = (0..10_000).map(|i| Vec3::new(i as f32, (i % 7) as f32, -(i as f32))).collect();
+    let mut out = vec![Vec3::zero(); points.len()];
+
+    c.bench_function("transform_points 10k", |bench| bench.iter(|| t.transform_points(black_box(&points), &mut out)));
+    c.bench_function("transform_vectors 10k", |bench| bench.iter(|| t.transform_vectors(black_box(&points), &mut out)));
+}
+
+criterion_group!(benches, transforms, batches);
+criterion_main!(benches);
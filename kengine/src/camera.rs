@@ -0,0 +1,153 @@
+use wgpu::util::DeviceExt;
+use crate::math::{Mat3, Mat4, Transform4D, Vec3, Vec4};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective { fovy: f32, near: f32, far: f32 },
+    // `height` is the height of the view volume in world units, the width follows the aspect ratio.
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+// `transform` places the camera in the world. Like the projections in Mat4 the camera looks down its
+// local -z axis with +y up. With `reverse_z` near maps to depth 1 and far to 0, for reverse-Z depth buffers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub transform: Transform4D,
+    pub projection: Projection,
+    pub aspect: f32,
+    pub reverse_z: bool,
+}
+
+// Layout of the camera uniform block, binding 0 of its bind group:
+//
+//     layout(set = 1, binding = 0) uniform Camera {
+//         mat4 u_view_proj;
+//         vec4 u_position;
+//     };
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view_proj: Mat4,
+    pub position: Vec4,
+}
+
+// The uniform buffer holding a CameraUniform and the bind group exposing it to vertex and fragment shaders.
+pub struct CameraBinding {
+    pub buffer: wgpu::Buffer,
+    pub layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl Camera {
+    pub fn perspective(fovy: f32, aspect: f32, near: f32, far: f32) -> Camera {
+        Camera {
+            transform: Transform4D::identity(),
+            projection: Projection::Perspective { fovy, near, far },
+            aspect,
+            reverse_z: false,
+        }
+    }
+
+    pub fn orthographic(height: f32, aspect: f32, near: f32, far: f32) -> Camera {
+        Camera {
+            transform: Transform4D::identity(),
+            projection: Projection::Orthographic { height, near, far },
+            aspect,
+            reverse_z: false,
+        }
+    }
+
+    // Moves the camera to `eye` and turns it towards `target`.
+    pub fn look_at(&mut self, eye: Vec3, target: Vec3, up: Vec3) {
+        let f = (target - eye).normalize();
+        let s = Vec3::cross(f, up).normalize();
+        let u = Vec3::cross(s, f);
+        self.transform = Transform4D::from_mat_vec(Mat3::from_cols(s, u, -f), eye);
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.transform.translation()
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        -self.transform.mat3().c2.normalize()
+    }
+
+    // A zero height leaves the aspect ratio alone, minimised windows have no shape.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.aspect = width as f32 / height as f32;
+        }
+    }
+
+    // World to view space.
+    pub fn view(&self) -> Transform4D {
+        self.transform.inv()
+    }
+
+    pub fn projection_matrix(&self) -> Mat4 {
+        match self.projection {
+            Projection::Perspective { fovy, near, far } if self.reverse_z => Mat4::perspective_reverse_z(fovy, self.aspect, near, far),
+            Projection::Perspective { fovy, near, far } => Mat4::perspective(fovy, self.aspect, near, far),
+            Projection::Orthographic { height, near, far } => {
+                let (h, w) = (0.5 * height, 0.5 * height * self.aspect);
+                // Swapping the planes reverses an orthographic depth range.
+                if self.reverse_z {
+                    Mat4::orthographic(-w, w, -h, h, far, near)
+                } else {
+                    Mat4::orthographic(-w, w, -h, h, near, far)
+                }
+            }
+        }
+    }
+
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection_matrix() * Mat4::from(self.view())
+    }
+}
+
+impl CameraUniform {
+    pub fn new(camera: &Camera) -> CameraUniform {
+        CameraUniform {
+            view_proj: camera.view_projection(),
+            position: Vec4::point(camera.position()),
+        }
+    }
+}
+
+impl CameraBinding {
+    pub fn new(device: &wgpu::Device, camera: &Camera) -> CameraBinding {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::bytes_of(&CameraUniform::new(camera)),
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("camera_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        CameraBinding { buffer, layout, bind_group }
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&CameraUniform::new(camera)));
+    }
+}
@@ -0,0 +1,380 @@
+use crate::math::{ApproxEq, Vec4};
+use std::slice;
+
+// Colour spaces. LinearRgba is the working space: blending, lighting and anything handed to the GPU happen
+// there. Srgba holds the gamma encoded values found in images and colour pickers, Hsv and Hsl are built on
+// top of it, and Oklab is a perceptual space for mixing and gradients. Components are nominally in 0..1,
+// hues are in degrees and alpha is always linear.
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Srgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub a: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+    pub a: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+// The sRGB transfer functions (IEC 61966-2-1) for a single channel.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl LinearRgba {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> LinearRgba {
+        LinearRgba { r, g, b, a }
+    }
+
+    pub fn rgb(r: f32, g: f32, b: f32) -> LinearRgba {
+        LinearRgba { r, g, b, a: 1.0 }
+    }
+
+    pub fn black() -> LinearRgba {
+        LinearRgba::rgb(0.0, 0.0, 0.0)
+    }
+
+    pub fn white() -> LinearRgba {
+        LinearRgba::rgb(1.0, 1.0, 1.0)
+    }
+
+    pub fn transparent() -> LinearRgba {
+        LinearRgba::new(0.0, 0.0, 0.0, 0.0)
+    }
+
+    pub fn to_srgba(&self) -> Srgba {
+        Srgba::new(linear_to_srgb(self.r), linear_to_srgb(self.g), linear_to_srgb(self.b), self.a)
+    }
+
+    pub fn to_oklab(&self) -> Oklab {
+        Oklab::from_linear(*self)
+    }
+
+    pub fn with_alpha(&self, a: f32) -> LinearRgba {
+        LinearRgba { a, ..*self }
+    }
+
+    pub fn premultiply(&self) -> LinearRgba {
+        LinearRgba::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    // Fully transparent colours have lost their colour, they come back as transparent black.
+    pub fn unpremultiply(&self) -> LinearRgba {
+        if self.a == 0.0 {
+            return LinearRgba::transparent();
+        }
+        let inv = 1.0 / self.a;
+        LinearRgba::new(self.r * inv, self.g * inv, self.b * inv, self.a)
+    }
+
+    // Porter-Duff `src` over `dst` for straight (not premultiplied) alpha.
+    pub fn over(src: LinearRgba, dst: LinearRgba) -> LinearRgba {
+        LinearRgba::over_premultiplied(src.premultiply(), dst.premultiply()).unpremultiply()
+    }
+
+    pub fn over_premultiplied(src: LinearRgba, dst: LinearRgba) -> LinearRgba {
+        let k = 1.0 - src.a;
+        LinearRgba::new(src.r + k * dst.r, src.g + k * dst.g, src.b + k * dst.b, src.a + k * dst.a)
+    }
+
+    // Channel-wise product, for tinting.
+    pub fn multiply(a: LinearRgba, b: LinearRgba) -> LinearRgba {
+        LinearRgba::new(a.r * b.r, a.g * b.g, a.b * b.b, a.a * b.a)
+    }
+
+    pub fn lerp(a: LinearRgba, b: LinearRgba, t: f32) -> LinearRgba {
+        let mix = |x: f32, y: f32| x + t * (y - x);
+        LinearRgba::new(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a))
+    }
+
+    // Relative luminance with the Rec. 709 primaries sRGB uses.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+}
+
+impl Srgba {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Srgba {
+        Srgba { r, g, b, a }
+    }
+
+    pub fn rgb(r: f32, g: f32, b: f32) -> Srgba {
+        Srgba { r, g, b, a: 1.0 }
+    }
+
+    pub fn from_rgba8(c: [u8; 4]) -> Srgba {
+        let f = |x: u8| x as f32 / 255.0;
+        Srgba::new(f(c[0]), f(c[1]), f(c[2]), f(c[3]))
+    }
+
+    // Clamps to 0..1 and rounds to the nearest step.
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        let f = |x: f32| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [f(self.r), f(self.g), f(self.b), f(self.a)]
+    }
+
+    // 0xRRGGBBAA, as written in most style sheets and editors.
+    pub fn from_hex(hex: u32) -> Srgba {
+        Srgba::from_rgba8(hex.to_be_bytes())
+    }
+
+    pub fn to_linear(&self) -> LinearRgba {
+        LinearRgba::new(srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b), self.a)
+    }
+}
+
+impl Hsv {
+    pub fn new(h: f32, s: f32, v: f32, a: f32) -> Hsv {
+        Hsv { h, s, v, a }
+    }
+
+    pub fn from_srgba(c: Srgba) -> Hsv {
+        let (max, min) = (c.r.max(c.g).max(c.b), c.r.min(c.g).min(c.b));
+        let delta = max - min;
+        let s = if max > 0.0 { delta / max } else { 0.0 };
+        Hsv::new(hue(c, max, delta), s, max, c.a)
+    }
+
+    pub fn to_srgba(&self) -> Srgba {
+        let c = self.v * self.s;
+        from_hue(self.h, c, self.v - c, self.a)
+    }
+}
+
+impl Hsl {
+    pub fn new(h: f32, s: f32, l: f32, a: f32) -> Hsl {
+        Hsl { h, s, l, a }
+    }
+
+    pub fn from_srgba(c: Srgba) -> Hsl {
+        let (max, min) = (c.r.max(c.g).max(c.b), c.r.min(c.g).min(c.b));
+        let delta = max - min;
+        let l = 0.5 * (max + min);
+        let s = if delta > 0.0 { delta / (1.0 - (2.0 * l - 1.0).abs()) } else { 0.0 };
+        Hsl::new(hue(c, max, delta), s, l, c.a)
+    }
+
+    pub fn to_srgba(&self) -> Srgba {
+        let c = (1.0 - (2.0 * self.l - 1.0).abs()) * self.s;
+        from_hue(self.h, c, self.l - 0.5 * c, self.a)
+    }
+}
+
+// Hue in degrees shared by Hsv and Hsl, zero for greys.
+fn hue(c: Srgba, max: f32, delta: f32) -> f32 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+    let h = if max == c.r {
+        (c.g - c.b) / delta
+    } else if max == c.g {
+        (c.b - c.r) / delta + 2.0
+    } else {
+        (c.r - c.g) / delta + 4.0
+    };
+    (60.0 * h).rem_euclid(360.0)
+}
+
+// Rebuilds rgb from the hue, the chroma `c` and the offset `m` added to every channel.
+fn from_hue(h: f32, c: f32, m: f32, a: f32) -> Srgba {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    Srgba::new(r + m, g + m, b + m, a)
+}
+
+// Matrices from Björn Ottosson, "A perceptual color space for image processing" (2020).
+impl Oklab {
+    pub fn new(l: f32, a: f32, b: f32, alpha: f32) -> Oklab {
+        Oklab { l, a, b, alpha }
+    }
+
+    #[allow(clippy::excessive_precision)]
+    pub fn from_linear(c: LinearRgba) -> Oklab {
+        let l = 0.412_221_470_8 * c.r + 0.536_332_536_3 * c.g + 0.051_445_992_9 * c.b;
+        let m = 0.211_903_498_2 * c.r + 0.680_699_545_1 * c.g + 0.107_396_956_6 * c.b;
+        let s = 0.088_302_461_9 * c.r + 0.281_718_837_6 * c.g + 0.629_978_700_5 * c.b;
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+        Oklab::new(
+            0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+            1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+            0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+            c.a,
+        )
+    }
+
+    #[allow(clippy::excessive_precision)]
+    pub fn to_linear(&self) -> LinearRgba {
+        let l = self.l + 0.396_337_777_4 * self.a + 0.215_803_757_3 * self.b;
+        let m = self.l - 0.105_561_345_8 * self.a - 0.063_854_172_8 * self.b;
+        let s = self.l - 0.089_484_177_5 * self.a - 1.291_485_548_0 * self.b;
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+        LinearRgba::new(
+            4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+            -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+            -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s,
+            self.alpha,
+        )
+    }
+
+    // Perceptually even gradient between two colours.
+    pub fn lerp(a: Oklab, b: Oklab, t: f32) -> Oklab {
+        let mix = |x: f32, y: f32| x + t * (y - x);
+        Oklab::new(mix(a.l, b.l), mix(a.a, b.a), mix(a.b, b.b), mix(a.alpha, b.alpha))
+    }
+}
+
+impl From<Srgba> for LinearRgba {
+    fn from(c: Srgba) -> LinearRgba {
+        c.to_linear()
+    }
+}
+
+impl From<LinearRgba> for Srgba {
+    fn from(c: LinearRgba) -> Srgba {
+        c.to_srgba()
+    }
+}
+
+impl From<Hsv> for Srgba {
+    fn from(c: Hsv) -> Srgba {
+        c.to_srgba()
+    }
+}
+
+impl From<Srgba> for Hsv {
+    fn from(c: Srgba) -> Hsv {
+        Hsv::from_srgba(c)
+    }
+}
+
+impl From<Hsl> for Srgba {
+    fn from(c: Hsl) -> Srgba {
+        c.to_srgba()
+    }
+}
+
+impl From<Srgba> for Hsl {
+    fn from(c: Srgba) -> Hsl {
+        Hsl::from_srgba(c)
+    }
+}
+
+impl From<Hsv> for LinearRgba {
+    fn from(c: Hsv) -> LinearRgba {
+        c.to_srgba().to_linear()
+    }
+}
+
+impl From<Hsl> for LinearRgba {
+    fn from(c: Hsl) -> LinearRgba {
+        c.to_srgba().to_linear()
+    }
+}
+
+impl From<Oklab> for LinearRgba {
+    fn from(c: Oklab) -> LinearRgba {
+        c.to_linear()
+    }
+}
+
+impl From<LinearRgba> for Oklab {
+    fn from(c: LinearRgba) -> Oklab {
+        Oklab::from_linear(c)
+    }
+}
+
+// Shader inputs, uniforms and vertex colours take linear values.
+impl From<LinearRgba> for [f32; 4] {
+    fn from(c: LinearRgba) -> [f32; 4] {
+        [c.r, c.g, c.b, c.a]
+    }
+}
+
+impl From<[f32; 4]> for LinearRgba {
+    fn from(c: [f32; 4]) -> LinearRgba {
+        LinearRgba::new(c[0], c[1], c[2], c[3])
+    }
+}
+
+impl From<LinearRgba> for Vec4 {
+    fn from(c: LinearRgba) -> Vec4 {
+        Vec4::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+// wgpu expects linear values for clear colours and blend constants, it encodes them itself when the
+// target has an sRGB format.
+impl From<LinearRgba> for wgpu::Color {
+    fn from(c: LinearRgba) -> wgpu::Color {
+        wgpu::Color { r: c.r as f64, g: c.g as f64, b: c.b as f64, a: c.a as f64 }
+    }
+}
+
+impl ApproxEq for LinearRgba {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
+
+impl ApproxEq for Srgba {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
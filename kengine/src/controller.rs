@@ -0,0 +1,287 @@
+use winit::event::*;
+use crate::camera::Camera;
+use crate::math::{Mat3, Transform4D, Vec3};
+
+// Scroll wheels report lines, touchpads report pixels.
+const PIXELS_PER_LINE: f64 = 20.0;
+// Keeps the view from reaching straight up or down, where yaw stops being defined.
+const PITCH_LIMIT: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+// Turns winit input into camera motion. Events are collected as they arrive and applied in `update`, where
+// movement is scaled by the frame time `dt` in seconds and mouse motion is used as accumulated since the last
+// frame, so the result does not depend on the frame rate. Both event methods return true when they used
+// the event.
+pub trait CameraController {
+    fn window_event(&mut self, event: &WindowEvent) -> bool;
+    fn device_event(&mut self, event: &DeviceEvent) -> bool;
+    fn update(&mut self, camera: &mut Camera, dt: f32);
+}
+
+// Free flight: WASD (or the arrow keys) move along the view direction, Space/E and Shift/Q move up and down
+// and dragging with the right mouse button looks around.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlyController {
+    pub speed: f32,
+    pub sensitivity: f32,
+    look: Look,
+    movement: Movement,
+    mouse: (f32, f32),
+    looking: bool,
+}
+
+// Walking: like FlyController but movement stays in the horizontal plane, the mouse always looks (the
+// cursor is expected to be grabbed) and pitch is clamped to `max_pitch` either way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirstPersonController {
+    pub speed: f32,
+    pub sensitivity: f32,
+    pub max_pitch: f32,
+    look: Look,
+    movement: Movement,
+    mouse: (f32, f32),
+}
+
+// Circles `target` at `distance`: dragging with the left mouse button orbits and the wheel zooms, each line
+// scaling the distance by 1 - `zoom_speed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitController {
+    pub target: Vec3,
+    pub distance: f32,
+    pub sensitivity: f32,
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    look: Look,
+    mouse: (f32, f32),
+    scroll: f32,
+    dragging: bool,
+}
+
+// Yaw about the world y axis and pitch about the camera's x axis, both zero when looking down -z.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Look {
+    yaw: f32,
+    pitch: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Movement {
+    forward: bool,
+    back: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+}
+
+impl Look {
+    fn from_forward(f: Vec3) -> Look {
+        Look { yaw: (-f.x).atan2(-f.z), pitch: f.y.clamp(-1.0, 1.0).asin() }
+    }
+
+    fn turn(&mut self, (dx, dy): (f32, f32), sensitivity: f32, max_pitch: f32) {
+        self.yaw = (self.yaw - dx * sensitivity).rem_euclid(2.0 * std::f32::consts::PI);
+        self.pitch = (self.pitch - dy * sensitivity).max(-max_pitch).min(max_pitch);
+    }
+
+    fn rotation(&self) -> Mat3 {
+        Mat3::rot_y(self.yaw) * Mat3::rot_x(self.pitch)
+    }
+}
+
+impl Movement {
+    fn key(&mut self, input: &KeyboardInput) -> bool {
+        let pressed = input.state == ElementState::Pressed;
+        match input.virtual_keycode {
+            Some(VirtualKeyCode::W) | Some(VirtualKeyCode::Up) => self.forward = pressed,
+            Some(VirtualKeyCode::S) | Some(VirtualKeyCode::Down) => self.back = pressed,
+            Some(VirtualKeyCode::A) | Some(VirtualKeyCode::Left) => self.left = pressed,
+            Some(VirtualKeyCode::D) | Some(VirtualKeyCode::Right) => self.right = pressed,
+            Some(VirtualKeyCode::Space) | Some(VirtualKeyCode::E) => self.up = pressed,
+            Some(VirtualKeyCode::LShift) | Some(VirtualKeyCode::Q) => self.down = pressed,
+            _ => return false,
+        }
+        true
+    }
+
+    // Sum of the unit directions for the held keys, normalised so diagonals are not faster.
+    fn direction(&self, forward: Vec3, right: Vec3, up: Vec3) -> Vec3 {
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        let d = axis(self.forward, self.back) * forward + axis(self.right, self.left) * right + axis(self.up, self.down) * up;
+        if d.length() > 0.0 {
+            d.normalize()
+        } else {
+            d
+        }
+    }
+}
+
+fn mouse_motion(event: &DeviceEvent) -> Option<(f32, f32)> {
+    match event {
+        DeviceEvent::MouseMotion { delta } => Some((delta.0 as f32, delta.1 as f32)),
+        _ => None,
+    }
+}
+
+impl FlyController {
+    pub fn new(camera: &Camera, speed: f32, sensitivity: f32) -> FlyController {
+        FlyController {
+            speed,
+            sensitivity,
+            look: Look::from_forward(camera.forward()),
+            movement: Movement::default(),
+            mouse: (0.0, 0.0),
+            looking: false,
+        }
+    }
+}
+
+impl CameraController for FlyController {
+    fn window_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => self.movement.key(input),
+            WindowEvent::MouseInput { state, button: MouseButton::Right, .. } => {
+                self.looking = *state == ElementState::Pressed;
+                true
+            }
+            WindowEvent::Focused(false) => {
+                self.movement = Movement::default();
+                self.looking = false;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn device_event(&mut self, event: &DeviceEvent) -> bool {
+        match mouse_motion(event) {
+            Some((dx, dy)) if self.looking => {
+                self.mouse = (self.mouse.0 + dx, self.mouse.1 + dy);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        self.look.turn(self.mouse, self.sensitivity, PITCH_LIMIT);
+        self.mouse = (0.0, 0.0);
+        let rotation = self.look.rotation();
+        let direction = self.movement.direction(-rotation.c2, rotation.c0, Vec3::y_axis());
+        let position = camera.position() + (self.speed * dt) * direction;
+        camera.transform = Transform4D::from_mat_vec(rotation, position);
+    }
+}
+
+impl FirstPersonController {
+    pub fn new(camera: &Camera, speed: f32, sensitivity: f32) -> FirstPersonController {
+        FirstPersonController {
+            speed,
+            sensitivity,
+            max_pitch: PITCH_LIMIT,
+            look: Look::from_forward(camera.forward()),
+            movement: Movement::default(),
+            mouse: (0.0, 0.0),
+        }
+    }
+}
+
+impl CameraController for FirstPersonController {
+    fn window_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => self.movement.key(input),
+            WindowEvent::Focused(false) => {
+                self.movement = Movement::default();
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn device_event(&mut self, event: &DeviceEvent) -> bool {
+        match mouse_motion(event) {
+            Some((dx, dy)) => {
+                self.mouse = (self.mouse.0 + dx, self.mouse.1 + dy);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        self.look.turn(self.mouse, self.sensitivity, self.max_pitch.min(PITCH_LIMIT));
+        self.mouse = (0.0, 0.0);
+        let (sin, cos) = self.look.yaw.sin_cos();
+        let forward = Vec3::new(-sin, 0.0, -cos);
+        let right = Vec3::new(cos, 0.0, -sin);
+        let direction = self.movement.direction(forward, right, Vec3::zero());
+        let position = camera.position() + (self.speed * dt) * direction;
+        camera.transform = Transform4D::from_mat_vec(self.look.rotation(), position);
+    }
+}
+
+impl OrbitController {
+    // Starts from where `camera` is, turning it towards `target`. A camera sitting on the target keeps its
+    // direction and is backed off to the minimum distance.
+    pub fn new(camera: &Camera, target: Vec3, sensitivity: f32) -> OrbitController {
+        let min_distance = 0.01;
+        let offset = target - camera.position();
+        let distance = offset.length();
+        let forward = if distance > 0.0 { (1.0 / distance) * offset } else { camera.forward() };
+        OrbitController {
+            target,
+            distance: distance.max(min_distance),
+            sensitivity,
+            zoom_speed: 0.1,
+            min_distance,
+            max_distance: f32::MAX,
+            look: Look::from_forward(forward),
+            mouse: (0.0, 0.0),
+            scroll: 0.0,
+            dragging: false,
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn window_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                self.dragging = *state == ElementState::Pressed;
+                true
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(p) => (p.y / PIXELS_PER_LINE) as f32,
+                };
+                true
+            }
+            WindowEvent::Focused(false) => {
+                self.dragging = false;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn device_event(&mut self, event: &DeviceEvent) -> bool {
+        match mouse_motion(event) {
+            Some((dx, dy)) if self.dragging => {
+                self.mouse = (self.mouse.0 + dx, self.mouse.1 + dy);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn update(&mut self, camera: &mut Camera, _dt: f32) {
+        self.look.turn(self.mouse, self.sensitivity, PITCH_LIMIT);
+        self.mouse = (0.0, 0.0);
+        let zoom = (1.0 - self.zoom_speed).powf(self.scroll);
+        self.distance = (self.distance * zoom).max(self.min_distance).min(self.max_distance);
+        self.scroll = 0.0;
+        let rotation = self.look.rotation();
+        camera.transform = Transform4D::from_mat_vec(rotation, self.target + self.distance * rotation.c2);
+    }
+}
@@ -0,0 +1,100 @@
+// How depth is stored and tested. Standard depth clears to 1 and keeps the nearest fragment with Less,
+// reverse-Z clears to 0 and keeps it with Greater, which together with a float format spreads precision
+// evenly over distance. The camera has to project to match, see Camera::reverse_z.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthConfig {
+    pub format: wgpu::TextureFormat,
+    pub compare: wgpu::CompareFunction,
+    pub clear: f32,
+}
+
+// The depth buffer frames are drawn with, sized to the render target.
+pub struct DepthTexture {
+    pub config: DepthConfig,
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+}
+
+impl DepthConfig {
+    pub const STANDARD: DepthConfig = DepthConfig {
+        format: wgpu::TextureFormat::Depth32Float,
+        compare: wgpu::CompareFunction::Less,
+        clear: 1.0,
+    };
+    pub const REVERSE_Z: DepthConfig = DepthConfig {
+        format: wgpu::TextureFormat::Depth32Float,
+        compare: wgpu::CompareFunction::Greater,
+        clear: 0.0,
+    };
+
+    pub fn is_reversed(&self) -> bool {
+        matches!(self.compare, wgpu::CompareFunction::Greater | wgpu::CompareFunction::GreaterEqual)
+    }
+
+    pub fn has_stencil(&self) -> bool {
+        self.format == wgpu::TextureFormat::Depth24PlusStencil8
+    }
+
+    // Depth testing and writing for pipelines drawing into the matching DepthTexture.
+    pub fn depth_stencil_state(&self) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: self.format,
+            depth_write_enabled: true,
+            depth_compare: self.compare,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+            clamp_depth: false,
+        }
+    }
+}
+
+impl Default for DepthConfig {
+    fn default() -> DepthConfig {
+        DepthConfig::STANDARD
+    }
+}
+
+impl DepthTexture {
+    // A zero sized target gets a 1x1 texture, wgpu refuses empty ones.
+    pub fn new(device: &wgpu::Device, config: DepthConfig, width: u32, height: u32) -> DepthTexture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+            label: Some("depth_texture"),
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        DepthTexture { config, texture, view }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        *self = DepthTexture::new(device, self.config, width, height);
+    }
+
+    // Clears depth, and stencil when there is one, at the start of the pass.
+    pub fn attachment(&self) -> wgpu::RenderPassDepthStencilAttachmentDescriptor {
+        wgpu::RenderPassDepthStencilAttachmentDescriptor {
+            attachment: &self.view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(self.config.clear),
+                store: true,
+            }),
+            stencil_ops: if self.config.has_stencil() {
+                Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: true,
+                })
+            } else {
+                None
+            },
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::{error, fmt, io};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+    // No adapter can drive the requested surface, or none at all for headless rendering.
+    NoAdapter,
+    DeviceRequest(wgpu::RequestDeviceError),
+    Window(winit::error::OsError),
+    ImageDecode(image::ImageError),
+    ImageEncode(image::ImageError),
+    // Images are uploaded as 8-bit RGBA, deeper formats would be silently truncated.
+    UnsupportedPixelFormat(image::ColorType),
+    ShaderCompile { label: String, reason: String },
+    // The swap chain was lost and could not be recreated.
+    SurfaceLost,
+    OutOfMemory,
+    // Reading a buffer back from the GPU failed.
+    BufferMap(wgpu::BufferAsyncError),
+    // Mesh attributes or indices that do not fit together.
+    InvalidMesh(String),
+    Io { path: PathBuf, source: io::Error },
+    // Malformed OBJ or MTL, `line` counts from 1. `path` is the file when the source was read from one.
+    ObjParse { path: Option<PathBuf>, line: usize, reason: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoAdapter => write!(f, "no suitable graphics adapter was found"),
+            Error::DeviceRequest(_) => write!(f, "failed to open the graphics device"),
+            Error::Window(_) => write!(f, "failed to create the window"),
+            Error::ImageDecode(_) => write!(f, "failed to decode the image"),
+            Error::ImageEncode(_) => write!(f, "failed to encode the image"),
+            Error::UnsupportedPixelFormat(format) => write!(f, "unsupported pixel format {:?}", format),
+            Error::ShaderCompile { label, reason } => write!(f, "failed to load shader {}: {}", label, reason),
+            Error::SurfaceLost => write!(f, "the swap chain was lost and could not be recreated"),
+            Error::OutOfMemory => write!(f, "out of memory for the next frame"),
+            Error::BufferMap(_) => write!(f, "failed to map the readback buffer"),
+            Error::InvalidMesh(reason) => write!(f, "invalid mesh: {}", reason),
+            Error::Io { path, .. } => write!(f, "failed to read {}", path.display()),
+            Error::ObjParse { path: Some(path), line, reason } => {
+                write!(f, "failed to parse {} line {}: {}", path.display(), line, reason)
+            }
+            Error::ObjParse { path: None, line, reason } => write!(f, "failed to parse line {}: {}", line, reason),
+        }
+    }
+}
+
+impl Error {
+    // The message followed by those of its sources, separated by ": ", for printing at the top level.
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = error::Error::source(self);
+        while let Some(e) = source {
+            report.push_str(": ");
+            report.push_str(&e.to_string());
+            source = e.source();
+        }
+        report
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::DeviceRequest(e) => Some(e),
+            Error::Window(e) => Some(e),
+            Error::ImageDecode(e) | Error::ImageEncode(e) => Some(e),
+            Error::BufferMap(e) => Some(e),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for Error {
+    fn from(e: wgpu::RequestDeviceError) -> Error {
+        Error::DeviceRequest(e)
+    }
+}
+
+impl From<winit::error::OsError> for Error {
+    fn from(e: winit::error::OsError) -> Error {
+        Error::Window(e)
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Error {
+        Error::ImageDecode(e)
+    }
+}
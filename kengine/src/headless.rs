@@ -0,0 +1,156 @@
+use std::path::Path;
+use futures::executor::block_on;
+use crate::error::{Error, Result};
+use crate::renderer::{App, Context};
+
+// Offscreen rendering for CI and thumbnails: frames are drawn into a texture and copied back to a
+// buffer, no window or display needed.
+pub struct Headless {
+    pub context: Context,
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    buffer: wgpu::Buffer,
+    padded_bytes_per_row: u32,
+}
+
+const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+// Offscreen frames are not paced by a display, updates step as if running at 60 Hz so output is reproducible.
+const HEADLESS_DT: f32 = 1.0 / 60.0;
+
+impl Headless {
+    // Prefers a hardware adapter on the primary backends and falls back to whatever the secondary ones
+    // offer, which includes software rasterisers.
+    pub async fn new(width: u32, height: u32) -> Result<Headless> {
+        assert!(width > 0 && height > 0, "Headless target needs a non-zero size");
+        let mut found = None;
+        for &backends in &[wgpu::BackendBit::PRIMARY, wgpu::BackendBit::SECONDARY] {
+            let instance = wgpu::Instance::new(backends);
+            let adapter = instance.request_adapter(
+                &wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: None,
+                },
+            ).await;
+            if let Some(adapter) = adapter {
+                found = Some((instance, adapter));
+                break;
+            }
+        }
+        let (instance, adapter) = found.ok_or(Error::NoAdapter)?;
+        let size = winit::dpi::PhysicalSize::new(width, height);
+        let context = Context::new(instance, adapter, HEADLESS_FORMAT, size).await?;
+
+        let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HEADLESS_FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+            label: Some("headless_target"),
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let padded_bytes_per_row = padded_bytes_per_row(4 * width);
+        let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("headless_readback"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Ok(Headless {
+            context,
+            texture,
+            view,
+            buffer,
+            padded_bytes_per_row,
+        })
+    }
+
+    // Runs one update and render of `app` into the target.
+    pub fn render<A: App>(&mut self, app: &mut A) {
+        app.update(&mut self.context, HEADLESS_DT);
+        let mut encoder = self.context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Headless Encoder"),
+        });
+        self.context.update_camera();
+        app.render(&self.context, &mut encoder, &self.view);
+        self.context.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    // Copies the target back to the CPU, waiting for the GPU to finish the frames submitted so far.
+    pub fn read_rgba8(&self) -> Result<image::RgbaImage> {
+        let size = self.context.size;
+        let mut encoder = self.context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::BufferCopyView {
+                buffer: &self.buffer,
+                layout: wgpu::TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: self.padded_bytes_per_row,
+                    rows_per_image: size.height,
+                },
+            },
+            wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth: 1,
+            },
+        );
+        self.context.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = self.buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        self.context.device.poll(wgpu::Maintain::Wait);
+        block_on(mapping).map_err(Error::BufferMap)?;
+        let pixels = {
+            let data = slice.get_mapped_range();
+            unpad_rows(&data, 4 * size.width as usize, self.padded_bytes_per_row as usize)
+        };
+        self.buffer.unmap();
+        Ok(image::RgbaImage::from_raw(size.width, size.height, pixels).expect("readback holds the whole target"))
+    }
+
+    // Renders `frames` frames and reads back every one of them.
+    pub fn render_frames<A: App>(&mut self, app: &mut A, frames: u32) -> Result<Vec<image::RgbaImage>> {
+        (0..frames)
+            .map(|_| {
+                self.render(app);
+                self.read_rgba8()
+            })
+            .collect()
+    }
+}
+
+// Initialises `app` against a `width` by `height` offscreen target and returns its first `frames` frames.
+pub fn run_headless<A: App>(mut app: A, width: u32, height: u32, frames: u32) -> Result<Vec<image::RgbaImage>> {
+    let _ = env_logger::try_init();
+    let mut headless = block_on(Headless::new(width, height))?;
+    app.init(&mut headless.context)?;
+    headless.render_frames(&mut app, frames)
+}
+
+pub fn save_png(image: &image::RgbaImage, path: impl AsRef<Path>) -> Result<()> {
+    image.save_with_format(path, image::ImageFormat::Png).map_err(Error::ImageEncode)
+}
+
+// Buffer copies need every row to start at a multiple of COPY_BYTES_PER_ROW_ALIGNMENT.
+pub(crate) fn padded_bytes_per_row(bytes_per_row: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (bytes_per_row + align - 1) / align * align
+}
+
+pub(crate) fn unpad_rows(data: &[u8], bytes_per_row: usize, padded_bytes_per_row: usize) -> Vec<u8> {
+    data.chunks(padded_bytes_per_row).flat_map(|row| &row[..bytes_per_row]).copied().collect()
+}
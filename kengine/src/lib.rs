@@ -1,4 +1,19 @@
-mod math;
+pub mod math;
+pub mod colour;
+pub mod camera;
+pub mod controller;
+pub mod depth;
+pub mod mesh;
+pub mod material;
+pub mod obj;
+pub mod error;
+pub mod texture;
+pub mod renderer;
+pub mod headless;
+
+pub use error::{Error, Result};
+pub use renderer::{run, App, Context, Renderer};
+pub use headless::{run_headless, Headless};
 
 #[cfg(test)]
 mod tests {
@@ -19,6 +34,52 @@ mod tests {
         assert_eq!(Vec3::cross(x, y), z);
     }
 
+    #[test]
+    fn vec2_tests() {
+        let eps = 1e-4;
+        let v = Vec2::new(3.0, 4.0);
+        assert_eq!(v.length(), 5.0);
+        assert!((v.normalize().length() - 1.0).abs() < eps);
+        assert_eq!(Vec2::dot(Vec2::x_axis(), Vec2::y_axis()), 0.0);
+        assert_eq!(Vec2::cross(Vec2::x_axis(), Vec2::y_axis()), 1.0);
+        assert_eq!(Vec2::x_axis().perp(), Vec2::y_axis());
+        assert_eq!(Vec2::lerp(Vec2::zero(), v, 0.5), Vec2::new(1.5, 2.0));
+        assert_eq!(Vec2::min(v, Vec2::new(4.0, 1.0)), Vec2::new(3.0, 1.0));
+        assert_eq!(Vec2::max(v, Vec2::new(4.0, 1.0)), Vec2::new(4.0, 4.0));
+        assert_eq!(v - Vec2::one(), Vec2::new(2.0, 3.0));
+        assert_eq!(v + -v, Vec2::zero());
+        assert_eq!(v[1], 4.0);
+        assert_eq!(v.extend(5.0), Vec3::new(3.0, 4.0, 5.0));
+        assert_eq!(v.extend(5.0).truncate(), v);
+        assert_eq!(Vec2::from([3.0, 4.0]), v);
+    }
+
+    #[test]
+    fn vec4_tests() {
+        let eps = 1e-4;
+        let v = Vec4::new(1.0, 2.0, 2.0, 4.0);
+        assert_eq!(v.length(), 5.0);
+        assert!((v.normalize().length() - 1.0).abs() < eps);
+        assert_eq!(Vec4::dot(v, Vec4::w_axis()), 4.0);
+        assert_eq!(Vec4::lerp(Vec4::zero(), v, 0.5), 0.5 * v);
+        assert_eq!(Vec4::min(v, Vec4::one()), Vec4::one());
+        assert_eq!(Vec4::max(v, Vec4::one()), v);
+        assert_eq!(v - Vec4::one(), Vec4::new(0.0, 1.0, 1.0, 3.0));
+        assert_eq!(v + -v, Vec4::zero());
+        assert_eq!(v[3], 4.0);
+        assert_eq!(v.truncate(), Vec3::new(1.0, 2.0, 2.0));
+        assert_eq!(v.project(), Vec3::new(0.25, 0.5, 0.5));
+        assert_eq!(<[f32; 4]>::from(v), [1.0, 2.0, 2.0, 4.0]);
+        // points are translated, directions are not
+        let transform = Transform4D::from_mat_vec(Mat3::rot_z(PI / 2.0), Vec3::new(1.0, 2.0, 3.0));
+        assert!((transform * Vec4::point(Vec3::x_axis())).truncate().approx_eq(&(transform * Vec3::x_axis()), eps));
+        assert!((transform * Vec4::dir(Vec3::x_axis())).truncate().approx_eq(&Vec3::y_axis(), eps));
+        assert_eq!((transform * Vec4::dir(Vec3::x_axis())).w, 0.0);
+        let proj = Mat4::perspective(PI / 2.0, 1.0, 0.1, 100.0);
+        let p = Vec3::new(1.0, 2.0, -3.0);
+        assert!((proj * Vec4::point(p)).project().approx_eq(&proj.project_point(p), eps));
+    }
+
     #[test]
     fn mat3_tests() {
         let eps: f32 = 1e-4;
@@ -27,33 +88,1576 @@ mod tests {
             Vec3::new(3.0, 4.0, 5.0),
             Vec3::new(3.0, 6.0, 10.0)
         );
-        let inv = mat.inv();
-        assert_eq!(mat * inv, Mat3::identity());
+        let inv = mat.try_inv().unwrap();
+        assert!((mat * inv).approx_eq(&Mat3::identity(), eps));
+        assert_eq!(mat.det(), 1.0);
         // transformations
-        assert!(vec3_approx_eq(Mat3::rot_x(PI / 2.0) * Vec3::y_axis(), Vec3::z_axis(), eps));
-        assert!(vec3_approx_eq(Mat3::rot_z(PI / 2.0) * Vec3::x_axis(), Vec3::y_axis(), eps));
-        assert!(vec3_approx_eq(Mat3::rot_y(PI / 2.0) * Vec3::z_axis(), Vec3::x_axis(), eps));
-        assert!(vec3_approx_eq(Mat3::rot(PI / 2.0, Vec3::x_axis()) * Vec3::y_axis(), Vec3::z_axis(), eps));
-        assert!(vec3_approx_eq(Mat3::rot(PI / 2.0, Vec3::z_axis()) * Vec3::x_axis(), Vec3::y_axis(), eps));
-        assert!(vec3_approx_eq(Mat3::rot(PI / 2.0, Vec3::y_axis()) * Vec3::z_axis(), Vec3::x_axis(), eps));
-        assert!(vec3_approx_eq(Mat3::rot_y(PI / 2.0) * Mat3::rot_x(PI / 2.0) * Vec3::y_axis(), Vec3::x_axis(), eps));
-        assert!(vec3_approx_eq((Mat3::rot_y(PI / 2.0) * Mat3::rot_x(PI / 2.0)).inv() * Vec3::x_axis(), Vec3::y_axis(), eps));
-        assert!(vec3_approx_eq(Mat3::reflect(Vec3::x_axis()) * Vec3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, 1.0, 0.0), eps));
-        assert!(vec3_approx_eq(Mat3::invol(Vec3::x_axis()) * Vec3::new(1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), eps));
-        assert!(vec3_approx_eq(Mat3::scale(2.0, 2.0, 2.0) * Vec3::new(0.5, 0.5, 0.5), Vec3::one(), eps));
-        assert!(vec3_approx_eq(Mat3::scale_along_dir(2.0, Vec3::x_axis()) * Vec3::one(), Vec3::new(2.0, 1.0, 1.0), eps));
-        assert!(vec3_approx_eq(Mat3::skew(PI / 4.0, Vec3::x_axis(), Vec3::y_axis()) * Vec3::y_axis(), Vec3::new(1.0, 1.0, 0.0), eps));
+        assert!((Mat3::rot_x(PI / 2.0) * Vec3::y_axis()).approx_eq(&Vec3::z_axis(), eps));
+        assert!((Mat3::rot_z(PI / 2.0) * Vec3::x_axis()).approx_eq(&Vec3::y_axis(), eps));
+        assert!((Mat3::rot_y(PI / 2.0) * Vec3::z_axis()).approx_eq(&Vec3::x_axis(), eps));
+        assert!((Mat3::rot(PI / 2.0, Vec3::x_axis()) * Vec3::y_axis()).approx_eq(&Vec3::z_axis(), eps));
+        assert!((Mat3::rot(PI / 2.0, Vec3::z_axis()) * Vec3::x_axis()).approx_eq(&Vec3::y_axis(), eps));
+        assert!((Mat3::rot(PI / 2.0, Vec3::y_axis()) * Vec3::z_axis()).approx_eq(&Vec3::x_axis(), eps));
+        assert!((Mat3::rot_y(PI / 2.0) * Mat3::rot_x(PI / 2.0) * Vec3::y_axis()).approx_eq(&Vec3::x_axis(), eps));
+        assert!(((Mat3::rot_y(PI / 2.0) * Mat3::rot_x(PI / 2.0)).inv() * Vec3::x_axis()).approx_eq(&Vec3::y_axis(), eps));
+        assert!((Mat3::reflect(Vec3::x_axis()) * Vec3::new(1.0, 1.0, 0.0)).approx_eq(&Vec3::new(-1.0, 1.0, 0.0), eps));
+        assert!((Mat3::invol(Vec3::x_axis()) * Vec3::new(1.0, 1.0, 0.0)).approx_eq(&Vec3::new(1.0, -1.0, 0.0), eps));
+        assert!((Mat3::scale(2.0, 2.0, 2.0) * Vec3::new(0.5, 0.5, 0.5)).approx_eq(&Vec3::one(), eps));
+        assert!((Mat3::scale_along_dir(2.0, Vec3::x_axis()) * Vec3::one()).approx_eq(&Vec3::new(2.0, 1.0, 1.0), eps));
+        assert!((Mat3::skew(PI / 4.0, Vec3::x_axis(), Vec3::y_axis()) * Vec3::y_axis()).approx_eq(&Vec3::new(1.0, 1.0, 0.0), eps));
+        // homogeneous vectors keep their w
+        assert_eq!(mat * Vec4::new(1.0, 0.0, 2.0, 1.0), Vec4::point(mat * Vec3::new(1.0, 0.0, 2.0)));
+        assert_eq!(mat * Vec4::new(1.0, 0.0, 2.0, 0.0), Vec4::dir(mat * Vec3::new(1.0, 0.0, 2.0)));
+        assert!((Mat3::rot_z(PI / 2.0) * Vec4::new(1.0, 0.0, 0.0, 0.25)).approx_eq(&Vec4::new(0.0, 1.0, 0.0, 0.25), eps));
+    }
+
+    #[test]
+    fn mat3_decomposition_tests() {
+        let eps = 1e-4;
+        let m = Mat3::from_cols(Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0), Vec3::new(7.0, 8.0, 10.0));
+        assert_eq!(m.transpose().row(0), m.c0);
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(m.trace(), 16.0);
+        assert!((m.det() - m.transpose().det()).abs() < eps);
+
+        // Drifted rotations come back to a rotation close to where they were.
+        let rot = Mat3::rot(1.3, Vec3::new(1.0, 2.0, 3.0).normalize());
+        let noise = Mat3::from_cols(Vec3::new(0.002, -0.001, 0.003), Vec3::new(0.001, 0.002, -0.002), Vec3::new(-0.003, 0.001, 0.001));
+        let drifted = rot + noise;
+        let fixed = drifted.orthonormalize();
+        assert!((fixed.transpose() * fixed).approx_eq(&Mat3::identity(), 1e-6));
+        assert!((fixed.det() - 1.0).abs() < 1e-6);
+        assert!(fixed.approx_eq(&rot, 1e-2));
+        assert_eq!(fixed.c0, drifted.c0.normalize());
+
+        // Inertia tensor of a box with sides 1, 2 and 4 turned by a known rotation.
+        let r = Mat3::rot(0.8, Vec3::new(-1.0, 0.5, 2.0).normalize());
+        let inertia = r * Mat3::scale(20.0, 17.0, 5.0) * r.transpose();
+        let (values, vectors) = inertia.symmetric_eigen();
+        assert!(values.approx_eq(&Vec3::new(20.0, 17.0, 5.0), eps));
+        assert!((vectors.det() - 1.0).abs() < eps);
+        for (value, vector, axis) in [(20.0, vectors.c0, r.c0), (17.0, vectors.c1, r.c1), (5.0, vectors.c2, r.c2)].iter() {
+            assert!((inertia * *vector).approx_eq(&(*value * *vector), 1e-3));
+            assert!((Vec3::dot(*vector, *axis).abs() - 1.0).abs() < eps);
+        }
+        let (values, vectors) = Mat3::identity().symmetric_eigen();
+        assert_eq!((values, vectors), (Vec3::one(), Mat3::identity()));
+        let (values, _) = Mat3::scale(1.0, 3.0, 2.0).symmetric_eigen();
+        assert_eq!(values, Vec3::new(3.0, 2.0, 1.0));
+
+        let (u, sigma, v) = m.svd();
+        assert!((u * Mat3::scale(sigma.x, sigma.y, sigma.z) * v.transpose()).approx_eq(&m, 1e-3));
+        assert!(sigma.x >= sigma.y && sigma.y >= sigma.z && sigma.z >= 0.0);
+        assert!(((sigma.x * sigma.y * sigma.z) - m.det().abs()).abs() < 1e-3);
+        // Rank deficient and reflected matrices.
+        let flat = Mat3::from_cols(Vec3::x_axis(), Vec3::new(2.0, 0.0, 0.0), Vec3::y_axis());
+        let (u, sigma, v) = flat.svd();
+        assert!(sigma.z.abs() < eps);
+        assert!((u * Mat3::scale(sigma.x, sigma.y, sigma.z) * v.transpose()).approx_eq(&flat, eps));
+        assert!((u.transpose() * u).approx_eq(&Mat3::identity(), eps));
+        let (u, sigma, v) = Mat3::zero().svd();
+        assert_eq!(sigma, Vec3::zero());
+        assert!((u.det() - 1.0).abs() < eps && (v.det() - 1.0).abs() < eps);
+
+        let stretch = Mat3::scale(2.0, 1.0, 0.5);
+        let (rot, s) = (r * stretch).polar();
+        assert!(rot.approx_eq(&r, eps));
+        assert!(s.approx_eq(&stretch, eps));
+        let (rot, s) = (r * Mat3::reflect(Vec3::x_axis()) * stretch).polar();
+        assert!((rot.det() + 1.0).abs() < eps);
+        assert!(s.approx_eq(&s.transpose(), eps));
+        assert!((rot * s).approx_eq(&(r * Mat3::reflect(Vec3::x_axis()) * stretch), eps));
     }
 
     #[test]
     fn transform4d_tests() {
         let eps = 1e-4;
         let transform = Transform4D::from_mat_vec(Mat3::rot_y(PI / 2.0) * Mat3::rot_x(PI / 2.0), Vec3::x_axis());
-        assert!(vec3_approx_eq(transform * Vec3::y_axis(), 2.0 * Vec3::x_axis(), eps));
-        assert!(vec3_approx_eq(transform.inv() * (2.0 * Vec3::x_axis()), Vec3::y_axis(), eps));
+        assert!((transform * Vec3::y_axis()).approx_eq(&(2.0 * Vec3::x_axis()), eps));
+        assert!((transform.inv() * (2.0 * Vec3::x_axis())).approx_eq(&Vec3::y_axis(), eps));
+        let other = Transform4D::from_mat_vec(Mat3::rot(0.7, Vec3::new(1.0, 2.0, 3.0).normalize()), Vec3::new(1.0, 2.0, 3.0));
+        assert!(Mat4::from(transform * other).approx_eq(&(Mat4::from(transform) * Mat4::from(other)), eps));
+        assert!(((transform * other) * Vec3::one()).approx_eq(&(transform * (other * Vec3::one())), eps));
+        assert!((transform.mat3() * Vec3::y_axis()).approx_eq(&Vec3::x_axis(), eps));
+        assert_eq!(transform.translation(), Vec3::x_axis());
+        // points, vectors and normals
+        let transform = Transform4D::from_mat_vec(Mat3::scale(2.0, 1.0, 1.0), Vec3::new(5.0, 6.0, 7.0));
+        let p = Vec3::new(1.0, 1.0, 0.0);
+        assert!(transform.transform_point(p).approx_eq(&Vec3::new(7.0, 7.0, 7.0), eps));
+        assert!(transform.transform_vector(p).approx_eq(&Vec3::new(2.0, 1.0, 0.0), eps));
+        // the normal of the plane x + y = 0 must stay perpendicular to the transformed plane
+        let n = transform.transform_normal(p);
+        let tangent = transform.transform_vector(Vec3::new(1.0, -1.0, 0.0));
+        assert!(Vec3::dot(n, tangent).abs() < eps);
+        assert!(n.approx_eq(&Vec3::new(0.5, 1.0, 0.0), eps));
+        let rotation = Transform4D::from_mat_vec(Mat3::rot_z(PI / 2.0), Vec3::one());
+        assert!(rotation.transform_normal(Vec3::x_axis()).approx_eq(&Vec3::y_axis(), eps));
+    }
+
+    #[test]
+    fn quat_tests() {
+        let eps = 1e-4;
+        let q = Quat::rot(PI / 2.0, Vec3::z_axis());
+        assert!((q * Vec3::x_axis()).approx_eq(&Vec3::y_axis(), eps));
+        assert!((q.inv() * Vec3::y_axis()).approx_eq(&Vec3::x_axis(), eps));
+        assert!((q.conj() * Vec3::y_axis()).approx_eq(&Vec3::x_axis(), eps));
+        // composition matches Mat3
+        let a = Quat::rot_y(PI / 2.0);
+        let b = Quat::rot_x(PI / 2.0);
+        assert!(((a * b) * Vec3::y_axis()).approx_eq(&Vec3::x_axis(), eps));
+        assert!((a * b).to_mat3().approx_eq(&(Mat3::rot_y(PI / 2.0) * Mat3::rot_x(PI / 2.0)), eps));
+        let axis = Vec3::new(1.0, 2.0, 3.0);
+        let axis = (1.0 / Vec3::dot(axis, axis).sqrt()) * axis;
+        assert!(Quat::rot(0.7, axis).to_mat3().approx_eq(&Mat3::rot(0.7, axis), eps));
+        // Mat3 round trips, covering every branch of from_mat3
+        for &m in &[Mat3::identity(), Mat3::rot_x(3.0), Mat3::rot_y(3.0), Mat3::rot_z(3.0), Mat3::rot(2.0, axis)] {
+            assert!(Quat::from_mat3(m).to_mat3().approx_eq(&m, eps));
+        }
+        assert!((Quat::new(1.0, 2.0, 3.0, 4.0).normalize().norm() - 1.0).abs() < eps);
+        // interpolation
+        let from = Quat::identity();
+        let to = Quat::rot_z(PI / 2.0);
+        assert!((Quat::slerp(from, to, 0.5) * Vec3::x_axis()).approx_eq(&(Quat::rot_z(PI / 4.0) * Vec3::x_axis()), eps));
+        assert!((Quat::slerp(from, to, 0.0) * Vec3::x_axis()).approx_eq(&Vec3::x_axis(), eps));
+        assert!((Quat::slerp(from, to, 1.0) * Vec3::x_axis()).approx_eq(&Vec3::y_axis(), eps));
+        assert!((Quat::slerp(from, -to, 0.5) * Vec3::x_axis()).approx_eq(&(Quat::rot_z(PI / 4.0) * Vec3::x_axis()), eps));
+        assert!((Quat::nlerp(from, to, 0.5) * Vec3::x_axis()).approx_eq(&(Quat::rot_z(PI / 4.0) * Vec3::x_axis()), eps));
+    }
+
+    #[test]
+    fn approx_tests() {
+        let a = Vec3::new(1.0, 1000.0, 0.0);
+        let b = Vec3::new(1.0 + 1e-7, 1000.0 + 1e-4, 1e-9);
+        assert!(!a.approx_eq(&b, 1e-5));
+        assert!(a.approx_eq(&b, 1e-3));
+        assert!(a.relative_eq(&b, 1e-6, 1e-6));
+        assert!(!a.relative_eq(&b, 1e-6, 1e-8));
+        assert!(a.ulps_eq(&b, 1e-6, 2));
+        assert!(!a.ulps_eq(&Vec3::new(1.0, 1000.5, 0.0), 1e-6, 4));
+        assert!(1.0f32.ulps_eq(&(1.0 + f32::EPSILON), 0.0, 1));
+        assert!(!(-0.0f32).ulps_eq(&1e-30, 0.0, 1000));
+        assert!((-0.0f32).ulps_eq(&1e-30, 1e-20, 0));
+        let nan = Vec3::new(f32::NAN, 0.0, 0.0);
+        assert!(!nan.approx_eq(&nan, 1.0) && !nan.relative_eq(&nan, 1.0, 1.0) && !nan.ulps_eq(&nan, 1.0, 100));
+        let t = Transform4D::from_mat_vec(Mat3::rot_y(0.5), Vec3::one());
+        assert!((t * t.inv()).approx_eq(&Transform4D::identity(), 1e-6));
+        assert!(Transform::identity().relative_eq(&Transform::from_scale(Vec3::new(1.0, 1.0, 1.0 + 1e-7)), 0.0, 1e-6));
+        assert!(Quat::identity().approx_eq(&Quat::rot_x(1e-6), 1e-6));
+
+        // Determinants, singular matrices and non-finite values.
+        let eps = 1e-6;
+        assert!((Mat3::rot(0.7, Vec3::new(1.0, 2.0, 3.0).normalize()).det() - 1.0).abs() < eps);
+        assert!((Mat3::scale(2.0, 3.0, 4.0).det() - 24.0).abs() < eps);
+        assert!((Mat3::reflect(Vec3::x_axis()).det() + 1.0).abs() < eps);
+        assert_eq!(Transform4D::from_mat_vec(Mat3::scale(2.0, 3.0, 4.0), Vec3::one()).det(), 24.0);
+        let flat = Mat3::from_cols(Vec3::x_axis(), Vec3::y_axis(), Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(flat.det(), 0.0);
+        assert_eq!(flat.try_inv(), None);
+        assert_eq!(Mat3::zero().try_inv(), None);
+        assert!(!flat.inv().is_finite());
+        let nearly_flat = Mat3::from_cols(Vec3::x_axis(), Vec3::y_axis(), Vec3::new(1.0, 1.0, 1e-8));
+        assert_eq!(nearly_flat.try_inv(), None);
+        // Uniformly tiny or huge matrices are still invertible, only the shape matters.
+        assert!(Mat3::scale(1e-4, 1e-4, 1e-4).try_inv().is_some());
+        assert!(Mat3::scale(1e4, 1e4, 1e4).try_inv().is_some());
+        assert_eq!(Transform4D::from_mat_vec(flat, Vec3::one()).try_inv(), None);
+        assert_eq!(Transform4D::from_mat_vec(Mat3::identity(), Vec3::new(f32::INFINITY, 0.0, 0.0)).try_inv(), None);
+        assert!(t.try_inv().unwrap().approx_eq(&t.inv(), 0.0));
+        assert_eq!(Mat4::from(Transform4D::from_mat_vec(flat, Vec3::one())).try_inv(), None);
+        let proj = Mat4::perspective(PI / 3.0, 1.5, 0.1, 100.0);
+        assert!((proj * proj.try_inv().unwrap()).approx_eq(&Mat4::identity(), 1e-5));
+        assert_eq!(DMat3::from(flat).try_inv(), None);
+        assert!(DTransform4D::from(t).try_inv().is_some());
+        assert!(!DVec3::new(0.0, f64::NAN, 0.0).is_finite());
+        assert!(Vec4::one().is_finite() && !Vec4::new(0.0, 0.0, 0.0, f32::NEG_INFINITY).is_finite());
+        assert!(!Vec2::new(f32::NAN, 0.0).is_finite() && !Quat::new(0.0, 0.0, 0.0, f32::NAN).is_finite());
+        assert!(!Mat4::from(Transform4D::from_mat_vec(flat.inv(), Vec3::zero())).is_finite());
+    }
+
+    #[test]
+    fn euler_tests() {
+        use EulerOrder::*;
+        let eps = 1e-4;
+        let orders = [Xyz, Xzy, Yxz, Yzx, Zxy, Zyx, Xyx, Xzx, Yxy, Yzy, Zxz, Zyz];
+
+        let e = EulerAngles::new(0.3, -0.5, 1.2, Xyz);
+        assert!(e.to_mat3().approx_eq(&(Mat3::rot_x(0.3) * Mat3::rot_y(-0.5) * Mat3::rot_z(1.2)), eps));
+        let e = EulerAngles::new(0.3, 0.5, 1.2, Zxz);
+        assert!(e.to_mat3().approx_eq(&(Mat3::rot_z(0.3) * Mat3::rot_x(0.5) * Mat3::rot_z(1.2)), eps));
+
+        for &order in orders.iter() {
+            // Inside the principal ranges the angles themselves come back, not only the rotation.
+            let middle = if order.is_proper() { 0.8 } else { -0.6 };
+            let e = EulerAngles::new(0.4, middle, -2.1, order);
+            let back = EulerAngles::from_mat3(e.to_mat3(), order);
+            assert!((back.first - 0.4).abs() < eps, "{:?} {:?}", order, back);
+            assert!((back.second - middle).abs() < eps, "{:?} {:?}", order, back);
+            assert!((back.third + 2.1).abs() < eps, "{:?} {:?}", order, back);
+            assert!(Mat3::from(e).approx_eq(&Quat::from(e).to_mat3(), eps), "{:?}", order);
+
+            // Arbitrary rotations, matrix and quaternion round trips.
+            let m = Mat3::rot(1.9, Vec3::new(-1.0, 2.0, 0.5).normalize());
+            assert!(EulerAngles::from_mat3(m, order).to_mat3().approx_eq(&m, eps), "{:?}", order);
+            let q = Quat::rot(2.7, Vec3::new(0.3, -0.2, 1.0).normalize());
+            let back = EulerAngles::from_quat(q, order).to_quat();
+            assert!((Quat::dot(back, q).abs() - 1.0).abs() < eps, "{:?}", order);
+
+            // Gimbal lock: the outer axes coincide, `third` is dropped and the rotation still survives.
+            let middle = if order.is_proper() { PI } else { PI / 2.0 };
+            for &b in [middle, -middle, 0.0].iter() {
+                let locked = EulerAngles::new(0.7, b, 0.4, order);
+                let back = EulerAngles::from_mat3(locked.to_mat3(), order);
+                assert!(back.to_mat3().approx_eq(&locked.to_mat3(), eps), "{:?} {} {:?}", order, b, back);
+                if order.is_proper() || b != 0.0 {
+                    assert_eq!(back.third, 0.0, "{:?} {}", order, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn f64_tests() {
+        let eps = 1e-9;
+        // precision that f32 cannot hold
+        let far = DVec3::new(1.0e8, 0.0, 0.0);
+        let offset = DVec3::new(0.25, 0.0, 0.0);
+        assert_eq!((far + offset - far).x, 0.25);
+        assert_ne!((far.as_vec3() + offset.as_vec3() - far.as_vec3()).x, 0.25);
+        // lossless widening, lossy narrowing
+        let v = Vec3::new(0.1, 0.2, 0.3);
+        assert_eq!(DVec3::from(v).as_vec3(), v);
+        assert_eq!(DVec3::new(0.1, 0.2, 0.3).as_vec3(), v);
+        let m = Mat3::rot(0.7, Vec3::new(1.0, 2.0, 3.0).normalize());
+        assert_eq!(DMat3::from(m).as_mat3(), m);
+        let t = Transform4D::from_mat_vec(m, v);
+        assert_eq!(DTransform4D::from(t).as_transform4d(), t);
+        // same API as the f32 types
+        let dm = DMat3::rot_y(std::f64::consts::PI / 2.0) * DMat3::rot_x(std::f64::consts::PI / 2.0);
+        assert!((dm * DVec3::y_axis() - DVec3::x_axis()).length() < eps);
+        assert!((dm.inv() * DVec3::x_axis() - DVec3::y_axis()).length() < eps);
+        let dt = DTransform4D::from_mat_vec(dm, DVec3::x_axis());
+        assert!((dt * DVec3::y_axis() - 2.0 * DVec3::x_axis()).length() < eps);
+        assert!((dt.inv() * (2.0 * DVec3::x_axis()) - DVec3::y_axis()).length() < eps);
+        let p = DVec3::new(1.0, -2.0, 3.0);
+        assert!(((dt * dt.inv()) * p - p).length() < eps);
+        assert!(((dt * dt) * p - dt * (dt * p)).length() < eps);
+    }
+
+    #[test]
+    fn mat4_tests() {
+        let eps = 1e-4;
+        let mat = Mat4::new(
+            2.0, 0.0, 1.0, 3.0,
+            1.0, 3.0, 0.0, -1.0,
+            0.0, 1.0, 4.0, 2.0,
+            1.0, 0.0, 2.0, 5.0,
+        );
+        assert!((mat * mat.inv()).approx_eq(&Mat4::identity(), eps));
+        assert!((mat.inv() * mat).approx_eq(&Mat4::identity(), eps));
+        assert!((mat.det() - 62.0).abs() < eps);
+        assert!((mat.transpose().det() - mat.det()).abs() < eps);
+        assert_eq!(mat.transpose().transpose(), mat);
+        assert_eq!(mat.transpose().row(0), mat.col(0));
+        // agrees with Transform4D
+        let transform = Transform4D::from_mat_vec(Mat3::rot_y(PI / 2.0) * Mat3::rot_x(PI / 2.0), Vec3::x_axis());
+        let m = Mat4::from(transform);
+        assert!(m.project_point(Vec3::y_axis()).approx_eq(&(transform * Vec3::y_axis()), eps));
+        assert!(m.inv().approx_eq(&Mat4::from(transform.inv()), eps));
+        // projections
+        let (near, far) = (0.1, 100.0);
+        let proj = Mat4::perspective(PI / 2.0, 2.0, near, far);
+        assert!(proj.project_point(Vec3::new(0.0, 0.0, -near)).approx_eq(&Vec3::zero(), eps));
+        assert!(proj.project_point(Vec3::new(0.0, 0.0, -far)).approx_eq(&Vec3::z_axis(), eps));
+        assert!(proj.project_point(Vec3::new(2.0, 1.0, -1.0)).approx_eq(&Vec3::new(1.0, 1.0, proj.project_point(Vec3::new(0.0, 0.0, -1.0)).z), eps));
+        let proj = Mat4::perspective_reverse_z(PI / 2.0, 2.0, near, far);
+        assert!(proj.project_point(Vec3::new(0.0, 0.0, -near)).approx_eq(&Vec3::z_axis(), eps));
+        assert!(proj.project_point(Vec3::new(0.0, 0.0, -far)).approx_eq(&Vec3::zero(), eps));
+        let proj = Mat4::perspective_infinite_reverse_z(PI / 2.0, 2.0, near);
+        assert!(proj.project_point(Vec3::new(0.0, 0.0, -near)).approx_eq(&Vec3::z_axis(), eps));
+        assert!(proj.project_point(Vec3::new(0.0, 0.0, -1e6)).z < eps);
+        let proj = Mat4::orthographic(-2.0, 2.0, -1.0, 1.0, near, far);
+        assert!(proj.project_point(Vec3::new(-2.0, -1.0, -near)).approx_eq(&Vec3::new(-1.0, -1.0, 0.0), eps));
+        assert!(proj.project_point(Vec3::new(2.0, 1.0, -far)).approx_eq(&Vec3::new(1.0, 1.0, 1.0), eps));
+        let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero(), Vec3::y_axis());
+        assert!(view.project_point(Vec3::zero()).approx_eq(&Vec3::new(0.0, 0.0, -5.0), eps));
+        assert!(view.project_point(Vec3::x_axis()).approx_eq(&Vec3::new(1.0, 0.0, -5.0), eps));
+        let view = Mat4::look_at(Vec3::new(3.0, 0.0, 0.0), Vec3::zero(), Vec3::y_axis());
+        assert!(view.project_point(Vec3::z_axis()).approx_eq(&Vec3::new(-1.0, 0.0, -3.0), eps));
+    }
+
+    #[test]
+    fn transform_tests() {
+        let eps = 1e-4;
+        let axis = Vec3::new(1.0, 2.0, 3.0).normalize();
+        let a = Transform::new(Vec3::new(1.0, 2.0, 3.0), Quat::rot(0.7, axis), Vec3::new(2.0, 3.0, 4.0));
+        let p = Vec3::new(-1.0, 0.5, 2.0);
+        assert!(a.transform_point(p).approx_eq(&(a.to_transform4d() * p), eps));
+        assert!(a.transform_vector(p).approx_eq(&a.to_transform4d().transform_vector(p), eps));
+        // decomposition round trips
+        let (b, shear) = Transform::decompose(&a.to_transform4d()).unwrap();
+        assert!(shear.approx_eq(&Vec3::zero(), eps));
+        assert!(b.translation.approx_eq(&a.translation, eps));
+        assert!(b.scale.approx_eq(&a.scale, eps));
+        assert!(b.rotation.to_mat3().approx_eq(&a.rotation.to_mat3(), eps));
+        // negative scale
+        let mirrored = Transform::new(Vec3::zero(), Quat::rot_z(0.5), Vec3::new(-2.0, 1.0, 1.0));
+        let b = Transform::from_transform4d(&mirrored.to_transform4d()).unwrap();
+        assert!(b.scale.approx_eq(&mirrored.scale, eps));
+        assert!(b.transform_point(p).approx_eq(&mirrored.transform_point(p), eps));
+        // shear
+        let sheared = Transform4D::from_mat_vec(Mat3::rot_y(0.3) * Mat3::skew(PI / 4.0, Vec3::x_axis(), Vec3::y_axis()) * Mat3::scale(2.0, 3.0, 1.0), Vec3::one());
+        let (b, shear) = Transform::decompose(&sheared).unwrap();
+        assert!(shear.approx_eq(&Vec3::new(1.0, 0.0, 0.0), eps));
+        assert!(b.scale.approx_eq(&Vec3::new(2.0, 3.0, 1.0), eps));
+        assert!(b.rotation.to_mat3().approx_eq(&Mat3::rot_y(0.3), eps));
+        // mirrored and sheared, the shear keeps its sign when the reflection moves into the x scale
+        let skew = Mat3::skew(PI / 4.0, Vec3::x_axis(), Vec3::y_axis()) * Mat3::skew(0.4, Vec3::x_axis(), Vec3::z_axis());
+        let m = Mat3::rot_y(0.3) * skew * Mat3::scale(-2.0, 3.0, 1.5);
+        let (b, shear) = Transform::decompose(&Transform4D::from_mat_vec(m, Vec3::one())).unwrap();
+        assert!(shear.approx_eq(&Vec3::new(1.0, 0.4_f32.tan(), 0.0), eps));
+        assert!(b.scale.approx_eq(&Vec3::new(-2.0, 3.0, 1.5), eps));
+        assert!(b.rotation.to_mat3().approx_eq(&Mat3::rot_y(0.3), eps));
+        let rebuilt = b.rotation.to_mat3() * Mat3::from_cols(Vec3::x_axis(), Vec3::new(shear.x, 1.0, 0.0), Vec3::new(shear.y, shear.z, 1.0)) * Mat3::scale(b.scale.x, b.scale.y, b.scale.z);
+        assert!(rebuilt.approx_eq(&m, eps));
+        // singular matrices have no decomposition
+        let flat = Transform::new(Vec3::zero(), Quat::rot_z(0.5), Vec3::new(1.0, 0.0, 2.0));
+        assert!(Transform::decompose(&flat.to_transform4d()).is_none());
+        assert!(Transform::from_transform4d(&Transform4D::from_mat_vec(Mat3::from_cols(Vec3::x_axis(), Vec3::y_axis(), Vec3::new(1.0, 1.0, 0.0)), Vec3::zero())).is_none());
+        assert!(Transform::decompose(&Transform4D::from_mat_vec(Mat3::scale(0.0, 0.0, 0.0), Vec3::one())).is_none());
+        // composition and inverse
+        let c = Transform::new(Vec3::new(0.0, -1.0, 2.0), Quat::rot_x(1.2), Vec3::new(2.0, 2.0, 2.0));
+        assert!((c * a).transform_point(p).approx_eq(&c.transform_point(a.transform_point(p)), eps));
+        assert!(Mat4::from((c * a).to_transform4d()).approx_eq(&(Mat4::from(c.to_transform4d()) * Mat4::from(a.to_transform4d())), eps));
+        assert!(c.inv().transform_point(c.transform_point(p)).approx_eq(&p, eps));
+        assert!((c * c.inv()).transform_point(p).approx_eq(&p, eps));
+        // interpolation
+        let from = Transform::identity();
+        let to = Transform::new(Vec3::new(2.0, 0.0, 0.0), Quat::rot_z(PI / 2.0), Vec3::new(3.0, 3.0, 3.0));
+        let mid = Transform::lerp(from, to, 0.5);
+        assert!(mid.translation.approx_eq(&Vec3::x_axis(), eps));
+        assert!(mid.scale.approx_eq(&Vec3::new(2.0, 2.0, 2.0), eps));
+        assert!(mid.rotation.to_mat3().approx_eq(&Mat3::rot_z(PI / 4.0), eps));
+    }
+
+    #[test]
+    fn ray_tests() {
+        let eps = 1e-4;
+        let ray = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::z_axis());
+        assert!(ray.at(2.0).approx_eq(&Vec3::new(0.0, 0.0, -3.0), eps));
+        // plane
+        let plane = Plane::from_point_normal(Vec3::zero(), Vec3::z_axis());
+        assert_eq!(ray.intersect_plane(&plane), Some(5.0));
+        assert_eq!(Ray::new(Vec3::z_axis(), Vec3::z_axis()).intersect_plane(&plane), None);
+        assert_eq!(Ray::new(Vec3::z_axis(), Vec3::x_axis()).intersect_plane(&plane), None);
+        // sphere
+        let sphere = Sphere::new(Vec3::zero(), 1.0);
+        assert_eq!(ray.intersect_sphere(&sphere), Some(4.0));
+        assert_eq!(Ray::new(Vec3::zero(), Vec3::z_axis()).intersect_sphere(&sphere), Some(0.0));
+        assert_eq!(Ray::new(Vec3::new(0.0, 2.0, -5.0), Vec3::z_axis()).intersect_sphere(&sphere), None);
+        assert_eq!(Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::z_axis()).intersect_sphere(&sphere), None);
+        // aabb
+        let aabb = Aabb::new(-Vec3::one(), Vec3::one());
+        assert_eq!(ray.intersect_aabb(&aabb), Some(4.0));
+        assert_eq!(Ray::new(Vec3::zero(), Vec3::x_axis()).intersect_aabb(&aabb), Some(0.0));
+        assert_eq!(Ray::new(Vec3::new(0.0, 2.0, -5.0), Vec3::z_axis()).intersect_aabb(&aabb), None);
+        assert_eq!(Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::z_axis()).intersect_aabb(&aabb), None);
+        let diagonal = Ray::new(Vec3::new(-3.0, -3.0, -3.0), Vec3::one());
+        assert_eq!(diagonal.intersect_aabb(&aabb), Some(2.0));
+        // obb
+        let obb = Obb::new(Vec3::new(0.0, 0.0, 3.0), Mat3::rot_z(PI / 4.0), Vec3::one());
+        let t = Ray::new(Vec3::new(-5.0, 0.0, 3.0), Vec3::x_axis()).intersect_obb(&obb).unwrap();
+        assert!((t - (5.0 - 2.0f32.sqrt())).abs() < eps);
+        assert_eq!(Ray::new(Vec3::new(-5.0, 1.5, 3.0), Vec3::x_axis()).intersect_obb(&obb), None);
+        // triangle
+        let tri = Triangle::new(Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(ray.intersect_triangle(&tri), Some(5.0));
+        assert_eq!(Ray::new(Vec3::new(0.0, 0.0, 5.0), -Vec3::z_axis()).intersect_triangle(&tri), Some(5.0));
+        assert_eq!(Ray::new(Vec3::new(0.9, 0.9, -5.0), Vec3::z_axis()).intersect_triangle(&tri), None);
+        assert_eq!(Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::z_axis()).intersect_triangle(&tri), None);
+        assert_eq!(Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::x_axis()).intersect_triangle(&tri), None);
+    }
+
+    #[test]
+    fn primitive_tests() {
+        let eps = 1e-4;
+        // plane
+        let plane = Plane::from_points(Vec3::zero(), Vec3::x_axis(), Vec3::y_axis());
+        assert!(plane.n.approx_eq(&Vec3::z_axis(), eps));
+        assert_eq!(plane.dist(Vec3::new(3.0, 4.0, -2.0)), -2.0);
+        assert!(plane.closest_point(Vec3::new(3.0, 4.0, -2.0)).approx_eq(&Vec3::new(3.0, 4.0, 0.0), eps));
+        assert!(Plane::new(2.0 * Vec3::y_axis(), -2.0).normalize().n.approx_eq(&Vec3::y_axis(), eps));
+        // sphere
+        let sphere = Sphere::new(Vec3::zero(), 1.0);
+        assert!(sphere.contains_point(Vec3::new(0.5, 0.5, 0.5)));
+        assert!(!sphere.contains_point(Vec3::one()));
+        assert_eq!(sphere.dist(Vec3::new(3.0, 0.0, 0.0)), 2.0);
+        assert!(sphere.intersects_sphere(&Sphere::new(Vec3::new(1.5, 0.0, 0.0), 0.5)));
+        assert!(!sphere.intersects_sphere(&Sphere::new(Vec3::new(1.6, 0.0, 0.0), 0.5)));
+        // aabb
+        let aabb = Aabb::from_points(&[Vec3::new(1.0, -1.0, 0.0), Vec3::new(-1.0, 1.0, 2.0), Vec3::zero()]);
+        assert_eq!(aabb, Aabb::new(Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 2.0)));
+        assert_eq!(aabb.center(), Vec3::z_axis());
+        assert_eq!(aabb.half_extents(), Vec3::one());
+        assert!(aabb.contains_point(Vec3::new(0.5, 0.5, 1.5)));
+        assert!(!aabb.contains_point(Vec3::new(0.5, 0.5, -0.5)));
+        assert_eq!(aabb.closest_point(Vec3::new(3.0, 0.0, 1.0)), Vec3::new(1.0, 0.0, 1.0));
+        assert_eq!(aabb.dist(Vec3::new(3.0, 0.0, 1.0)), 2.0);
+        assert_eq!(aabb.dist(aabb.center()), 0.0);
+        assert!(aabb.intersects_aabb(&Aabb::new(Vec3::one(), 2.0 * Vec3::one())));
+        assert!(!aabb.intersects_aabb(&Aabb::new(Vec3::new(1.1, 0.0, 0.0), 2.0 * Vec3::one())));
+        assert!(aabb.intersects_sphere(&Sphere::new(Vec3::new(2.0, 0.0, 1.0), 1.0)));
+        assert!(!aabb.intersects_sphere(&Sphere::new(Vec3::new(2.0, 2.0, 1.0), 1.0)));
+        assert!(Sphere::new(Vec3::new(2.0, 0.0, 1.0), 1.0).intersects_aabb(&aabb));
+        // obb
+        let obb = Obb::new(Vec3::zero(), Mat3::rot_z(PI / 4.0), Vec3::one());
+        assert!(obb.contains_point(Vec3::new(1.3, 0.0, 0.0)));
+        assert!(!obb.contains_point(Vec3::new(1.3, 1.3, 0.0)));
+        assert!(obb.closest_point(Vec3::new(3.0, 0.0, 0.0)).approx_eq(&Vec3::new(2.0f32.sqrt(), 0.0, 0.0), eps));
+        assert!((obb.dist(Vec3::new(2.0, 2.0, 0.0)) - (8.0f32.sqrt() - 1.0)).abs() < eps);
+        assert!(obb.intersects_sphere(&Sphere::new(Vec3::new(2.0, 0.0, 0.0), 0.6)));
+        assert!(!obb.intersects_sphere(&Sphere::new(Vec3::new(2.0, 0.0, 0.0), 0.5)));
+        let aligned = Obb::from_aabb(&Aabb::new(Vec3::new(1.3, -0.5, -0.5), Vec3::new(2.3, 0.5, 0.5)));
+        assert!(obb.intersects_obb(&aligned));
+        assert!(aligned.intersects_obb(&obb));
+        let aligned = Obb::from_aabb(&Aabb::new(Vec3::new(1.1, 1.1, -0.5), Vec3::new(2.0, 2.0, 0.5)));
+        assert!(!obb.intersects_obb(&aligned));
+        assert!(!aligned.intersects_obb(&obb));
+        // separated only along an edge-edge axis
+        let a = Obb::new(Vec3::zero(), Mat3::rot_x(PI / 4.0), Vec3::one());
+        let b = Obb::new(Vec3::new(0.0, 2.9, 0.0), Mat3::rot_z(PI / 4.0), Vec3::one());
+        assert!(!a.intersects_obb(&b));
+        let b = Obb::new(Vec3::new(0.0, 2.3, 0.0), Mat3::rot_z(PI / 4.0), Vec3::one());
+        assert!(a.intersects_obb(&b));
+        // triangle, one point per Voronoi region
+        let tri = Triangle::new(Vec3::zero(), Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0));
+        assert!(tri.normal().approx_eq(&Vec3::z_axis(), eps));
+        assert!(tri.plane().n.approx_eq(&Vec3::z_axis(), eps));
+        let cases = [
+            (Vec3::new(0.5, 0.5, 3.0), Vec3::new(0.5, 0.5, 0.0)),
+            (Vec3::new(-1.0, -1.0, 0.0), Vec3::zero()),
+            (Vec3::new(3.0, -1.0, 0.0), Vec3::new(2.0, 0.0, 0.0)),
+            (Vec3::new(-1.0, 3.0, 1.0), Vec3::new(0.0, 2.0, 0.0)),
+            (Vec3::new(1.0, -1.0, 0.0), Vec3::new(1.0, 0.0, 0.0)),
+            (Vec3::new(-1.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(2.0, 2.0, 0.0), Vec3::new(1.0, 1.0, 0.0)),
+        ];
+        for &(p, expected) in &cases {
+            assert!(tri.closest_point(p).approx_eq(&expected, eps));
+        }
+        assert_eq!(tri.dist(Vec3::new(0.5, 0.5, 3.0)), 3.0);
+        // capsule
+        let capsule = Capsule::new(Vec3::zero(), Vec3::new(0.0, 2.0, 0.0), 0.5);
+        assert!(capsule.contains_point(Vec3::new(0.4, 1.0, 0.0)));
+        assert!(capsule.contains_point(Vec3::new(0.0, 2.4, 0.0)));
+        assert!(!capsule.contains_point(Vec3::new(0.0, 2.6, 0.0)));
+        assert!((capsule.dist(Vec3::new(2.0, 1.0, 0.0)) - 1.5).abs() < eps);
+        assert!(capsule.intersects_sphere(&Sphere::new(Vec3::new(1.0, 1.0, 0.0), 0.6)));
+        assert!(!capsule.intersects_sphere(&Sphere::new(Vec3::new(1.0, 3.0, 0.0), 0.6)));
+        assert!(capsule.intersects_capsule(&Capsule::new(Vec3::new(-1.0, 1.0, 0.9), Vec3::new(1.0, 1.0, 0.9), 0.5)));
+        assert!(!capsule.intersects_capsule(&Capsule::new(Vec3::new(-1.0, 1.0, 1.1), Vec3::new(1.0, 1.0, 1.1), 0.5)));
+        // segments
+        assert_eq!(closest_point_on_segment(Vec3::new(1.0, 5.0, 0.0), Vec3::zero(), Vec3::new(0.0, 2.0, 0.0)), Vec3::new(0.0, 2.0, 0.0));
+        let (p, q) = closest_points_segment_segment(Vec3::zero(), Vec3::new(2.0, 0.0, 0.0), Vec3::new(1.0, 1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        assert!(p.approx_eq(&Vec3::x_axis(), eps));
+        assert!(q.approx_eq(&Vec3::new(1.0, 1.0, 0.0), eps));
+        let (p, q) = closest_points_segment_segment(Vec3::zero(), Vec3::x_axis(), Vec3::new(3.0, 1.0, 0.0), Vec3::new(4.0, 1.0, 0.0));
+        assert!(p.approx_eq(&Vec3::x_axis(), eps));
+        assert!(q.approx_eq(&Vec3::new(3.0, 1.0, 0.0), eps));
     }
 
-    fn vec3_approx_eq(a: Vec3, b: Vec3, eps: f32) -> bool {
-        (a.x - b.x).abs() < eps && (a.y - b.y).abs() < eps && (a.z - b.z).abs() < eps
+    #[test]
+    fn frustum_tests() {
+        let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero(), Vec3::y_axis());
+        for &proj in &[
+            Mat4::perspective(PI / 2.0, 1.0, 1.0, 10.0),
+            Mat4::perspective_reverse_z(PI / 2.0, 1.0, 1.0, 10.0),
+        ] {
+            let frustum = Frustum::from_mat4(&(proj * view));
+            assert!(frustum.contains_point(Vec3::zero()));
+            assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -4.9)));
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -5.1)));
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 4.5)));
+            assert!(!frustum.contains_point(Vec3::new(5.5, 0.0, 0.0)));
+            assert!(frustum.contains_point(Vec3::new(4.5, 0.0, 0.0)));
+            assert!(frustum.intersects_sphere(&Sphere::new(Vec3::new(5.5, 0.0, 0.0), 1.0)));
+            assert!(!frustum.intersects_sphere(&Sphere::new(Vec3::new(7.0, 0.0, 0.0), 1.0)));
+            assert!(!frustum.intersects_sphere(&Sphere::new(Vec3::new(0.0, 0.0, 8.0), 1.0)));
+            assert!(frustum.intersects_aabb(&Aabb::new(Vec3::new(4.0, -1.0, -1.0), Vec3::new(6.0, 1.0, 1.0))));
+            assert!(!frustum.intersects_aabb(&Aabb::new(Vec3::new(6.5, -1.0, -1.0), Vec3::new(7.5, 1.0, 1.0))));
+            assert!(!frustum.intersects_aabb(&Aabb::new(Vec3::new(-1.0, -1.0, -7.0), Vec3::new(1.0, 1.0, -6.0))));
+        }
+        // reverse-Z swaps near and far
+        let frustum = Frustum::from_mat4(&(Mat4::perspective_reverse_z(PI / 2.0, 1.0, 1.0, 10.0) * view));
+        assert!((frustum.planes[4].dist(Vec3::new(0.0, 0.0, -5.0))).abs() < 1e-4);
+        assert!((frustum.planes[5].dist(Vec3::new(0.0, 0.0, 4.0))).abs() < 1e-4);
+        // an infinite far plane culls nothing behind the near plane
+        let frustum = Frustum::from_mat4(&(Mat4::perspective_infinite_reverse_z(PI / 2.0, 1.0, 1.0) * view));
+        assert!(frustum.planes.iter().all(|p| p.n.x.is_finite() && p.d.is_finite()));
+        assert!(frustum.contains_point(Vec3::zero()));
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -1.0e6)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 4.5)));
+        assert!(!frustum.contains_point(Vec3::new(5.5, 0.0, 0.0)));
+        assert!(frustum.intersects_sphere(&Sphere::new(Vec3::new(0.0, 0.0, -500.0), 1.0)));
+        assert!(!frustum.intersects_sphere(&Sphere::new(Vec3::new(0.0, 0.0, 8.0), 1.0)));
+        assert!(frustum.intersects_aabb(&Aabb::new(Vec3::new(-1.0, -1.0, -900.0), Vec3::new(1.0, 1.0, -800.0))));
+        assert!(!frustum.intersects_aabb(&Aabb::new(Vec3::new(6.5, -1.0, -1.0), Vec3::new(7.5, 1.0, 1.0))));
+    }
+
+    #[test]
+    fn curves_tests() {
+        let eps = 1e-4;
+        let (a, b, c, d) = (Vec3::zero(), Vec3::new(1.0, 2.0, 0.0), Vec3::new(3.0, 2.0, 1.0), Vec3::new(4.0, 0.0, 1.0));
+        let points = vec![a, b, c, d, Vec3::new(5.0, -1.0, 3.0)];
+
+        let quad = QuadraticBezier::new(a, b, c);
+        assert_eq!(quad.point(0.0), a);
+        assert_eq!(quad.point(1.0), c);
+        assert!(quad.point(0.5).approx_eq(&(0.25 * a + 0.5 * b + 0.25 * c), eps));
+        let cubic = CubicBezier::new(a, b, c, d);
+        assert_eq!((cubic.point(0.0), cubic.point(1.0)), (a, d));
+        assert!(cubic.derivative(0.0).approx_eq(&(3.0 * (b - a)), eps));
+        assert!(cubic.point(-1.0).approx_eq(&a, 0.0) && cubic.point(2.0).approx_eq(&d, 0.0));
+
+        let linear = Linear::new(points.clone());
+        assert!(linear.point(0.125).approx_eq(&(0.5 * b), eps));
+        let catmull_rom = CatmullRom::new(points.clone());
+        let hermite = Hermite::new(points.clone(), vec![Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis(), Vec3::one(), -Vec3::x_axis()]);
+        for (i, p) in points.iter().enumerate() {
+            let t = i as f32 / 4.0;
+            assert!(catmull_rom.point(t).approx_eq(p, eps));
+            assert!(hermite.point(t).approx_eq(p, eps));
+            assert!(linear.point(t).approx_eq(p, eps));
+        }
+        assert!(hermite.derivative(0.25).approx_eq(&(4.0 * Vec3::y_axis()), eps));
+        assert!(catmull_rom.derivative(0.5).approx_eq(&(4.0 * 0.5 * (d - b)), eps));
+
+        // Evenly spaced collinear control points give a straight B-spline at constant speed.
+        let line = BSpline::new((0..6).map(|i| Vec3::new(i as f32, 0.0, 0.0)).collect());
+        assert!(line.point(0.0).approx_eq(&Vec3::x_axis(), eps));
+        assert!(line.point(0.5).approx_eq(&Vec3::new(2.5, 0.0, 0.0), eps));
+        assert!(line.derivative(0.3).approx_eq(&Vec3::new(3.0, 0.0, 0.0), eps));
+        assert!(line.second_derivative(0.7).approx_eq(&Vec3::zero(), eps));
+
+        // Derivatives agree with finite differences everywhere inside the segments.
+        let spline = BSpline::new(points);
+        let curves: [&dyn Curve; 6] = [&linear, &quad, &cubic, &hermite, &catmull_rom, &spline];
+        let h = 1e-3;
+        for curve in curves.iter() {
+            for &t in [0.1, 0.3, 0.45, 0.6, 0.9].iter() {
+                let d1 = (1.0 / (2.0 * h)) * (curve.point(t + h) - curve.point(t - h));
+                let d2 = (1.0 / (2.0 * h)) * (curve.derivative(t + h) - curve.derivative(t - h));
+                assert!(curve.derivative(t).relative_eq(&d1, 1e-2, 1e-2), "{} {:?} {:?}", t, curve.derivative(t), d1);
+                assert!(curve.second_derivative(t).relative_eq(&d2, 5e-2, 1e-2), "{} {:?} {:?}", t, curve.second_derivative(t), d2);
+            }
+        }
+
+        // Quarter circle of radius 2 as a cubic Bezier, its length is pi and curvature points at the centre.
+        let k = 0.552_284_8 * 2.0;
+        let arc = CubicBezier::new(Vec3::new(2.0, 0.0, 0.0), Vec3::new(2.0, k, 0.0), Vec3::new(k, 2.0, 0.0), Vec3::new(0.0, 2.0, 0.0));
+        let table = ArcLengthTable::new(&arc, 256);
+        assert!((table.length() - PI).abs() < 1e-3);
+        assert_eq!(table.distance_at(0.0), 0.0);
+        for &s in [0.0, 0.4, 1.5, 3.0].iter() {
+            let t = table.t_at_distance(s);
+            assert!((table.distance_at(t) - s).abs() < 1e-4);
+            assert!((ArcLengthTable::new(&arc, 256).t_at_fraction(s / table.length()) - t).abs() < 1e-5);
+        }
+        let halfway = arc.point(table.t_at_fraction(0.5));
+        assert!(halfway.approx_eq(&Vec3::new(2.0f32.sqrt(), 2.0f32.sqrt(), 0.0), 1e-3));
+        assert_eq!(table.t_at_distance(100.0), 1.0);
+
+        let frame = arc.frenet_frame(0.5);
+        assert!(frame.normal.approx_eq(&(-0.5 * frame.position).normalize(), 1e-3));
+        assert!(frame.binormal.approx_eq(&Vec3::z_axis(), eps));
+        assert!((frame.to_mat3().det() - 1.0).abs() < eps);
+        let straight = linear.frenet_frame(0.1);
+        assert!(Vec3::dot(straight.normal, straight.tangent).abs() < eps && (straight.normal.length() - 1.0).abs() < eps);
+
+        // Rotation minimizing frames of a planar curve keep the plane normal, along a helix they stay
+        // orthonormal and follow the curve.
+        let frames = rotation_minimizing_frames(&arc, 32, Vec3::z_axis());
+        assert_eq!(frames.len(), 32);
+        for f in frames.iter() {
+            assert!(f.normal.approx_eq(&Vec3::z_axis(), 1e-3) || f.binormal.approx_eq(&Vec3::z_axis(), 1e-3));
+            assert!((f.to_mat3().transpose() * f.to_mat3()).approx_eq(&Mat3::identity(), 1e-4));
+        }
+        let helix = CatmullRom::new((0..40).map(|i| Vec3::new((0.3 * i as f32).cos(), (0.3 * i as f32).sin(), 0.1 * i as f32)).collect());
+        let frames = rotation_minimizing_frames(&helix, 200, Vec3::z_axis());
+        for (i, f) in frames.iter().enumerate() {
+            assert!((f.to_mat3().transpose() * f.to_mat3()).approx_eq(&Mat3::identity(), 1e-4));
+            assert!(f.position.approx_eq(&helix.point(i as f32 / 199.0), eps));
+            assert!(f.tangent.approx_eq(&helix.derivative(i as f32 / 199.0).normalize(), eps));
+        }
+
+        // Nearest points on a line, a circle and the polyline corners.
+        let p = Vec3::new(1.5, 3.0, 0.0);
+        let t = line.nearest_t(p, 8);
+        assert!(line.point(t).approx_eq(&Vec3::new(1.5, 0.0, 0.0), eps));
+        assert!(arc.nearest_point(Vec3::new(3.0, 3.0, 0.0), 16).approx_eq(&Vec3::new(2.0f32.sqrt(), 2.0f32.sqrt(), 0.0), 1e-3));
+        assert!(arc.nearest_point(Vec3::new(5.0, -3.0, 0.0), 16).approx_eq(&Vec3::new(2.0, 0.0, 0.0), eps));
+        assert!(linear.nearest_point(Vec3::new(1.0, 3.0, 0.0), 64).approx_eq(&b, 1e-2));
+
+        // Orientation spline passes through its keys and stays normalized in between.
+        let keys = vec![Quat::identity(), Quat::rot_y(1.0), -Quat::rot(2.0, Vec3::new(1.0, 1.0, 0.0).normalize()), Quat::rot_z(0.5)];
+        let spline = QuatSpline::new(keys.clone());
+        for (i, q) in keys.iter().enumerate() {
+            let r = spline.rotation(i as f32 / 3.0);
+            assert!((Quat::dot(r, *q).abs() - 1.0).abs() < eps, "{} {:?} {:?}", i, r, q);
+        }
+        let mut prev = spline.rotation(0.0);
+        for i in 1..=100 {
+            let r = spline.rotation(i as f32 / 100.0);
+            assert!((r.norm() - 1.0).abs() < eps);
+            assert!(Quat::dot(prev, r) > 0.99);
+            prev = r;
+        }
+    }
+
+    #[test]
+    fn colour_tests() {
+        use super::colour::*;
+        let eps = 1e-5;
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < eps);
+        assert!((srgb_to_linear(0.5) - 0.214_041).abs() < eps);
+        assert!((linear_to_srgb(0.214_041) - 0.5).abs() < eps);
+        assert!((linear_to_srgb(0.002) - 0.002 * 12.92).abs() < eps);
+        for i in 0..=255u8 {
+            let c = Srgba::from_rgba8([i, 255 - i, i / 2, 255]);
+            assert_eq!(c.to_linear().to_srgba().to_rgba8(), [i, 255 - i, i / 2, 255]);
+            assert_eq!(Srgba::from(Hsv::from(c)).to_rgba8(), c.to_rgba8());
+            assert_eq!(Srgba::from(Hsl::from(c)).to_rgba8(), c.to_rgba8());
+            assert!(c.to_linear().to_oklab().to_linear().approx_eq(&c.to_linear(), 1e-4));
+        }
+        assert_eq!(Srgba::from_hex(0xff80_00cc).to_rgba8(), [255, 128, 0, 204]);
+        assert_eq!(Srgba::new(1.5, -0.2, 0.5, 1.0).to_rgba8(), [255, 0, 128, 255]);
+
+        let red = Srgba::rgb(1.0, 0.0, 0.0);
+        assert_eq!(Hsv::from(red), Hsv::new(0.0, 1.0, 1.0, 1.0));
+        assert_eq!(Hsl::from(red), Hsl::new(0.0, 1.0, 0.5, 1.0));
+        assert_eq!(Hsv::from(Srgba::rgb(0.0, 1.0, 0.0)).h, 120.0);
+        assert_eq!(Hsv::from(Srgba::rgb(0.0, 0.0, 1.0)).h, 240.0);
+        assert_eq!(Hsv::from(Srgba::rgb(1.0, 0.0, 1.0)).h, 300.0);
+        assert_eq!(Hsl::from(Srgba::rgb(0.5, 0.5, 0.5)), Hsl::new(0.0, 0.0, 0.5, 1.0));
+        assert!(Hsv::new(-240.0, 1.0, 1.0, 1.0).to_srgba().approx_eq(&Srgba::rgb(0.0, 1.0, 0.0), eps));
+        assert!(Hsl::new(420.0, 1.0, 0.5, 1.0).to_srgba().approx_eq(&Srgba::rgb(1.0, 1.0, 0.0), eps));
+
+        let white = LinearRgba::white().to_oklab();
+        assert!((white.l - 1.0).abs() < 1e-3 && white.a.abs() < 1e-3 && white.b.abs() < 1e-3);
+        let black = LinearRgba::black().to_oklab();
+        assert!(black.l.abs() < eps);
+        let mid = Oklab::lerp(LinearRgba::black().into(), LinearRgba::white().into(), 0.5).to_linear();
+        assert!(mid.r > 0.1 && mid.r < 0.2 && (mid.r - mid.b).abs() < 1e-3);
+
+        let c = LinearRgba::new(0.8, 0.4, 0.2, 0.5);
+        assert_eq!(c.premultiply(), LinearRgba::new(0.4, 0.2, 0.1, 0.5));
+        assert!(c.premultiply().unpremultiply().approx_eq(&c, eps));
+        assert_eq!(LinearRgba::new(0.3, 0.3, 0.3, 0.0).unpremultiply(), LinearRgba::transparent());
+        let dst = LinearRgba::rgb(0.0, 0.0, 1.0);
+        assert!(LinearRgba::over(c, dst).approx_eq(&LinearRgba::new(0.4, 0.2, 0.6, 1.0), eps));
+        assert!(LinearRgba::over(c.with_alpha(1.0), dst).approx_eq(&c.with_alpha(1.0), eps));
+        assert!(LinearRgba::over(LinearRgba::transparent(), dst).approx_eq(&dst, eps));
+        let half = LinearRgba::new(0.0, 1.0, 0.0, 0.5);
+        let over = LinearRgba::over(half, c);
+        assert!((over.a - 0.75).abs() < eps);
+        assert!(over.premultiply().approx_eq(&LinearRgba::over_premultiplied(half.premultiply(), c.premultiply()), eps));
+        assert_eq!(LinearRgba::lerp(LinearRgba::black(), dst, 0.5), LinearRgba::new(0.0, 0.0, 0.5, 1.0));
+        assert!((LinearRgba::white().luminance() - 1.0).abs() < eps);
+
+        let a: [f32; 4] = c.into();
+        assert_eq!(a, [0.8, 0.4, 0.2, 0.5]);
+        assert_eq!(LinearRgba::from(a), c);
+        assert_eq!(Vec4::from(c), Vec4::new(0.8, 0.4, 0.2, 0.5));
+        let w: wgpu::Color = LinearRgba::new(0.25, 0.5, 1.0, 0.0).into();
+        assert_eq!(w, wgpu::Color { r: 0.25, g: 0.5, b: 1.0, a: 0.0 });
+    }
+
+    #[test]
+    fn random_tests() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let seq: Vec<u32> = (0..16).map(|_| a.next_u32()).collect();
+        assert!(seq.iter().all(|&x| x == b.next_u32()));
+        assert_ne!(Rng::new(43).next_u32(), seq[0]);
+        assert_ne!(Rng::with_stream(42, 1).next_u32(), seq[0]);
+
+        let mut rng = Rng::new(7);
+        let mut counts = [0; 6];
+        for _ in 0..6000 {
+            let f = rng.next_f32();
+            assert!((0.0..1.0).contains(&f));
+            let r = rng.range(-2.0, 3.0);
+            assert!((-2.0..3.0).contains(&r));
+            counts[rng.below(6) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| c > 900 && c < 1100), "{:?}", counts);
+        let mut items: Vec<u32> = (0..50).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..50).collect::<Vec<u32>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..50).collect::<Vec<u32>>());
+
+        let n = Vec3::new(1.0, 2.0, -2.0).normalize();
+        let (mut mean, mut cosine) = (Vec3::zero(), 0.0);
+        for _ in 0..4000 {
+            let s = rng.on_unit_sphere();
+            assert!((s.length() - 1.0).abs() < 1e-5);
+            let p = rng.in_unit_sphere();
+            assert!(p.length() <= 1.0);
+            mean = mean + p;
+            let h = rng.on_hemisphere(n);
+            assert!((h.length() - 1.0).abs() < 1e-5 && Vec3::dot(h, n) >= 0.0);
+            let c = rng.cosine_hemisphere(n);
+            assert!((c.length() - 1.0).abs() < 1e-5 && Vec3::dot(c, n) >= 0.0);
+            cosine += Vec3::dot(c, n);
+        }
+        assert!((1.0 / 4000.0 * mean).length() < 0.05);
+        // The mean cosine is 2/3 under a cosine-weighted distribution and 1/2 under a uniform one.
+        assert!((cosine / 4000.0 - 2.0 / 3.0).abs() < 0.02);
+
+        let points = poisson_disk_2d(&mut Rng::new(3), 10.0, 6.0, 1.0);
+        assert!(points.len() > 40, "{}", points.len());
+        for (i, &p) in points.iter().enumerate() {
+            assert!(p.x >= 0.0 && p.x <= 10.0 && p.y >= 0.0 && p.y <= 6.0 && p.z == 0.0);
+            assert!(points[i + 1..].iter().all(|&q| (q - p).length() >= 1.0));
+        }
+        // Nowhere is far from a sample.
+        for _ in 0..200 {
+            let q = Vec3::new(rng.range(0.0, 10.0), rng.range(0.0, 6.0), 0.0);
+            assert!(points.iter().any(|&p| (p - q).length() < 2.0));
+        }
+        assert_eq!(points, poisson_disk_2d(&mut Rng::new(3), 10.0, 6.0, 1.0));
+
+        let size = Vec3::new(4.0, 3.0, 5.0);
+        let points = poisson_disk_3d(&mut Rng::new(3), size, 1.0);
+        assert!(points.len() > 30, "{}", points.len());
+        for (i, &p) in points.iter().enumerate() {
+            assert!(Vec3::min(p, Vec3::zero()) == Vec3::zero() && Vec3::max(p, size) == size);
+            assert!(points[i + 1..].iter().all(|&q| (q - p).length() >= 1.0));
+        }
+    }
+
+    #[test]
+    fn noise_tests() {
+        let mut rng = Rng::new(11);
+        let perlin = Perlin::new(1);
+        let simplex = Simplex::new(1);
+        let value = ValueNoise::new(1);
+        let worley = Worley::new(1);
+        let fbm = Fbm::new(Simplex::new(1), 5);
+        let ridged = Ridged::new(Perlin::new(1), 5);
+        assert_eq!(perlin.sample3(Vec3::new(3.0, -2.0, 7.0)), 0.0);
+        assert_eq!(perlin.sample2(Vec2::new(-4.0, 9.0)), 0.0);
+        assert_eq!(Fbm::new(perlin, 1).sample3(Vec3::new(0.3, 0.7, 1.9)), perlin.sample3(Vec3::new(0.3, 0.7, 1.9)));
+
+        let noises: [&dyn Noise; 4] = [&perlin, &simplex, &value, &fbm];
+        let (mut sums, mut differs) = ([0.0; 4], false);
+        for _ in 0..2000 {
+            let p = Vec3::new(rng.range(-100.0, 100.0), rng.range(-100.0, 100.0), rng.range(-100.0, 100.0));
+            let q = p + Vec3::new(1e-3, -1e-3, 1e-3);
+            for (noise, sum) in noises.iter().zip(sums.iter_mut()) {
+                let (a, b) = (noise.sample2(p.truncate()), noise.sample3(p));
+                assert!(a.abs() <= 1.0 && b.abs() <= 1.0, "{} {}", a, b);
+                assert!((b - noise.sample3(q)).abs() < 0.02);
+                *sum += b;
+            }
+            let w = (worley.sample2(p.truncate()), worley.sample3(p));
+            assert!(w.0 >= 0.0 && w.0 < 1.5 && w.1 >= 0.0 && w.1 < 1.8);
+            assert!((w.1 - worley.sample3(q)).abs() < 0.01);
+            let r = ridged.sample3(p);
+            assert!((0.0..=1.0).contains(&r));
+            differs |= Perlin::new(2).sample3(p) != perlin.sample3(p);
+        }
+        assert!(differs);
+        assert!(sums.iter().all(|&s| (s / 2000.0).abs() < 0.05), "{:?}", sums);
+        assert_eq!(Simplex::new(1).sample3(Vec3::new(1.5, 2.5, 3.5)), simplex.sample3(Vec3::new(1.5, 2.5, 3.5)));
+    }
+
+    #[test]
+    fn error_tests() {
+        use super::renderer::check_spirv;
+        use super::Error;
+        use std::error::Error as _;
+        let e = Error::from(image::load_from_memory(b"not an image").unwrap_err());
+        assert!(matches!(e, Error::ImageDecode(_)));
+        assert!(e.source().is_some());
+        assert_eq!(e.to_string(), "failed to decode the image");
+        let e = Error::UnsupportedPixelFormat(image::ColorType::Rgba16);
+        assert_eq!(e.to_string(), "unsupported pixel format Rgba16");
+        assert!(e.source().is_none());
+        assert!(Error::from(wgpu::RequestDeviceError).source().is_some());
+        assert_eq!(Error::BufferMap(wgpu::BufferAsyncError).to_string(), "failed to map the readback buffer");
+        let source = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let e = Error::Io { path: "level.obj".into(), source };
+        assert_eq!(e.report(), "failed to read level.obj: no such file");
+        assert_eq!(Error::NoAdapter.report(), "no suitable graphics adapter was found");
+
+        assert!(check_spirv("shader.vert.spv", include_bytes!("shader.vert.spv")).is_ok());
+        assert!(check_spirv("shader.frag.spv", include_bytes!("shader.frag.spv")).is_ok());
+        let e = check_spirv("empty", &[]).unwrap_err();
+        assert_eq!(e.to_string(), "failed to load shader empty: SPIR-V size is not a non-zero multiple of 4");
+        assert!(matches!(check_spirv("glsl", b"#version 450\n\0\0\0"), Err(Error::ShaderCompile { .. })));
+        assert!(check_spirv("odd", &[3, 2, 35, 7, 0]).is_err());
+    }
+
+    #[test]
+    fn headless_tests() {
+        use super::headless::*;
+        assert_eq!(padded_bytes_per_row(4), 256);
+        assert_eq!(padded_bytes_per_row(256), 256);
+        assert_eq!(padded_bytes_per_row(4 * 65), 512);
+        assert_eq!(padded_bytes_per_row(4 * 800), 3328);
+        let data: Vec<u8> = (0..3u8).flat_map(|row| (0..=255).map(move |i| if i < 12 { row * 12 + i } else { 255 })).collect();
+        assert_eq!(unpad_rows(&data, 12, 256), (0..36).collect::<Vec<u8>>());
+    }
+
+    // Needs an adapter, which CI machines without a GPU or software rasteriser do not have. Run it with
+    // `cargo test -- --ignored` where there is one.
+    #[test]
+    #[ignore]
+    fn headless_gpu_tests() {
+        use super::headless::*;
+        let mut target = futures::executor::block_on(Headless::new(70, 3)).unwrap();
+        struct Clear;
+        impl super::App for Clear {
+            fn init(&mut self, _context: &mut super::Context) -> super::Result<()> {
+                Ok(())
+            }
+
+            fn render(&mut self, _context: &super::Context, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
+                    color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                        attachment: target,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(super::colour::Srgba::from_rgba8([255, 128, 0, 255]).to_linear().into()),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+            }
+        }
+        let frames = target.render_frames(&mut Clear, 2).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].dimensions(), (70, 3));
+        assert!(frames[1].pixels().all(|p| p.0 == [255, 128, 0, 255]));
+    }
+
+    #[test]
+    fn camera_tests() {
+        use super::camera::*;
+        let eps = 1e-5;
+        let (eye, target) = (Vec3::new(1.0, 2.0, 5.0), Vec3::new(-1.0, 0.5, 0.0));
+        let mut camera = Camera::perspective(PI / 3.0, 16.0 / 9.0, 0.5, 50.0);
+        camera.look_at(eye, target, Vec3::y_axis());
+        assert!(Mat4::from(camera.view()).approx_eq(&Mat4::look_at(eye, target, Vec3::y_axis()), eps));
+        assert!(camera.position().approx_eq(&eye, eps));
+        assert!(camera.forward().approx_eq(&(target - eye).normalize(), eps));
+        let vp = camera.view_projection();
+        assert!(vp.approx_eq(&(Mat4::perspective(PI / 3.0, 16.0 / 9.0, 0.5, 50.0) * Mat4::look_at(eye, target, Vec3::y_axis())), eps));
+        let centre = vp.project_point(target);
+        assert!(centre.x.abs() < eps && centre.y.abs() < eps && centre.z > 0.0 && centre.z < 1.0);
+        assert!((vp.project_point(eye + 0.5 * camera.forward()).z).abs() < eps);
+        assert!((vp.project_point(eye + 50.0 * camera.forward()).z - 1.0).abs() < eps);
+
+        camera.resize(800, 400);
+        assert_eq!(camera.aspect, 2.0);
+        camera.resize(0, 0);
+        assert_eq!(camera.aspect, 2.0);
+        // Half the vertical field of view up is the top edge of the screen.
+        let up = Mat3::rot(PI / 6.0, Vec3::cross(camera.forward(), Vec3::y_axis()).normalize()) * camera.forward();
+        assert!((camera.view_projection().project_point(eye + 10.0 * up).y - 1.0).abs() < 1e-4);
+
+        let mut ortho = Camera::orthographic(4.0, 2.0, 0.0, 10.0);
+        ortho.look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero(), Vec3::y_axis());
+        assert!(ortho.view_projection().project_point(Vec3::new(4.0, 2.0, 0.0)).approx_eq(&Vec3::new(1.0, 1.0, 0.5), eps));
+        assert!(ortho.view_projection().project_point(Vec3::new(-4.0, -2.0, -5.0)).approx_eq(&Vec3::new(-1.0, -1.0, 1.0), eps));
+
+        let uniform = CameraUniform::new(&ortho);
+        assert_eq!(std::mem::size_of::<CameraUniform>(), 80);
+        assert_eq!(uniform.view_proj, ortho.view_projection());
+        assert_eq!(uniform.position, Vec4::new(0.0, 0.0, 5.0, 1.0));
+    }
+
+    #[test]
+    fn controller_tests() {
+        use super::camera::Camera;
+        use super::controller::*;
+        use winit::event::*;
+        let eps = 1e-4;
+        let device_id = unsafe { DeviceId::dummy() };
+        #[allow(deprecated)]
+        let key = |code, state| WindowEvent::KeyboardInput {
+            device_id,
+            input: KeyboardInput { scancode: 0, state, virtual_keycode: Some(code), modifiers: ModifiersState::empty() },
+            is_synthetic: true,
+        };
+        #[allow(deprecated)]
+        let button = |button, state| WindowEvent::MouseInput { device_id, state, button, modifiers: ModifiersState::empty() };
+        let motion = |x: f32, y: f32| DeviceEvent::MouseMotion { delta: (x as f64, y as f64) };
+        let mut start = Camera::perspective(PI / 4.0, 1.0, 0.1, 100.0);
+        start.look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero(), Vec3::y_axis());
+
+        // Holding W for a second moves `speed` units forward however the second is split into frames.
+        let (mut slow, mut fast) = (start, start);
+        let mut fly = FlyController::new(&start, 2.0, 0.01);
+        assert!(fly.window_event(&key(VirtualKeyCode::W, ElementState::Pressed)));
+        let mut fly_fast = fly.clone();
+        (0..10).for_each(|_| fly.update(&mut slow, 0.1));
+        (0..250).for_each(|_| fly_fast.update(&mut fast, 0.004));
+        assert!(slow.position().approx_eq(&Vec3::new(0.0, 0.0, 3.0), eps));
+        assert!(fast.position().approx_eq(&slow.position(), eps));
+        assert!(slow.forward().approx_eq(&-Vec3::z_axis(), eps));
+        fly.window_event(&key(VirtualKeyCode::W, ElementState::Released));
+        fly.update(&mut slow, 0.1);
+        assert!(slow.position().approx_eq(&Vec3::new(0.0, 0.0, 3.0), eps));
+        // Diagonals are no faster than straight lines.
+        fly.window_event(&key(VirtualKeyCode::D, ElementState::Pressed));
+        fly.window_event(&key(VirtualKeyCode::Space, ElementState::Pressed));
+        fly.update(&mut slow, 1.0);
+        assert!(slow.position().approx_eq(&Vec3::new(2.0_f32.sqrt(), 2.0_f32.sqrt(), 3.0), eps));
+        fly.window_event(&WindowEvent::Focused(false));
+        let before = slow.position();
+        fly.update(&mut slow, 1.0);
+        assert!(slow.position().approx_eq(&before, eps));
+        // The mouse only looks around while the right button is held, moving right turns right.
+        assert!(!fly.device_event(&motion(100.0, 0.0)));
+        assert!(fly.window_event(&button(MouseButton::Right, ElementState::Pressed)));
+        assert!(fly.device_event(&motion(50.0, 0.0)));
+        assert!(fly.device_event(&motion(50.0 * PI - 50.0, 0.0)));
+        fly.update(&mut slow, 0.016);
+        assert!(slow.forward().approx_eq(&Vec3::x_axis(), eps));
+        fly.device_event(&motion(0.0, -1000.0));
+        fly.update(&mut slow, 0.016);
+        assert!(slow.forward().y > 0.99 && slow.forward().y < 1.0);
+        assert!(Vec3::dot(slow.transform.mat3().c0, Vec3::y_axis()).abs() < eps);
+
+        // First person movement stays level when looking down and pitch stops at the limit.
+        let mut walker = start;
+        let mut fps = FirstPersonController::new(&start, 1.0, 0.01);
+        fps.max_pitch = PI / 4.0;
+        assert!(fps.device_event(&motion(0.0, 500.0)));
+        fps.window_event(&key(VirtualKeyCode::W, ElementState::Pressed));
+        fps.window_event(&key(VirtualKeyCode::Q, ElementState::Pressed));
+        fps.update(&mut walker, 1.5);
+        assert!(walker.forward().approx_eq(&Vec3::new(0.0, -1.0, -1.0).normalize(), eps));
+        assert!(walker.position().approx_eq(&Vec3::new(0.0, 0.0, 3.5), eps));
+        fps.device_event(&motion(0.0, -PI / 0.02));
+        fps.update(&mut walker, 0.0);
+        assert!(walker.forward().approx_eq(&Vec3::new(0.0, 1.0, -1.0).normalize(), eps));
+
+        // Orbiting keeps the target centred at the same distance, the wheel zooms within the limits.
+        let mut orbiting = start;
+        let mut orbit = OrbitController::new(&start, Vec3::zero(), 0.01);
+        assert!((orbit.distance - 5.0).abs() < eps);
+        orbit.update(&mut orbiting, 0.016);
+        assert!(orbiting.transform.approx_eq(&start.transform, eps));
+        assert!(!orbit.device_event(&motion(100.0, 0.0)));
+        orbit.window_event(&button(MouseButton::Left, ElementState::Pressed));
+        orbit.device_event(&motion(50.0 * PI, 0.0));
+        orbit.update(&mut orbiting, 0.016);
+        assert!(orbiting.position().approx_eq(&Vec3::new(-5.0, 0.0, 0.0), eps));
+        assert!(orbiting.forward().approx_eq(&Vec3::x_axis(), eps));
+        orbit.window_event(&button(MouseButton::Left, ElementState::Released));
+        assert!(!orbit.device_event(&motion(100.0, 100.0)));
+        orbit.window_event(&WindowEvent::MouseWheel { device_id, delta: MouseScrollDelta::LineDelta(0.0, 2.0), phase: TouchPhase::Moved, modifiers: Default::default() });
+        orbit.update(&mut orbiting, 0.016);
+        assert!((orbit.distance - 5.0 * 0.81).abs() < eps);
+        assert!(orbiting.position().approx_eq(&Vec3::new(-4.05, 0.0, 0.0), eps));
+        orbit.min_distance = 1.0;
+        orbit.window_event(&WindowEvent::MouseWheel {
+            device_id,
+            delta: MouseScrollDelta::PixelDelta(winit::dpi::LogicalPosition::new(0.0, 2000.0)),
+            phase: TouchPhase::Moved,
+            modifiers: Default::default(),
+        });
+        orbit.update(&mut orbiting, 0.016);
+        assert_eq!(orbit.distance, 1.0);
+        let mut on_target = start;
+        let mut orbit = OrbitController::new(&start, start.position(), 0.01);
+        assert_eq!(orbit.distance, orbit.min_distance);
+        orbit.update(&mut on_target, 0.016);
+        assert!(on_target.forward().approx_eq(&start.forward(), eps));
+        assert!(on_target.position().approx_eq(&(start.position() - orbit.min_distance * start.forward()), eps));
+    }
+
+    #[test]
+    fn depth_tests() {
+        use super::camera::Camera;
+        use super::depth::DepthConfig;
+        let eps = 1e-5;
+        assert_eq!(DepthConfig::default(), DepthConfig::STANDARD);
+        assert!(!DepthConfig::STANDARD.is_reversed() && DepthConfig::REVERSE_Z.is_reversed());
+        let state = DepthConfig::REVERSE_Z.depth_stencil_state();
+        assert_eq!(state.depth_compare, wgpu::CompareFunction::Greater);
+        assert!(state.depth_write_enabled && state.is_depth_enabled() && !state.clamp_depth);
+        let stencil = DepthConfig { format: wgpu::TextureFormat::Depth24PlusStencil8, ..DepthConfig::STANDARD };
+        assert!(stencil.has_stencil() && !DepthConfig::STANDARD.has_stencil());
+
+        // A reverse-Z camera clears to 0 at the far plane and keeps nearer fragments with Greater.
+        let (eye, target) = (Vec3::new(0.0, 1.0, 4.0), Vec3::zero());
+        let mut camera = Camera::perspective(PI / 3.0, 1.5, 0.5, 20.0);
+        camera.look_at(eye, target, Vec3::y_axis());
+        let mut reversed = camera;
+        reversed.reverse_z = true;
+        let near = eye + 0.5 * camera.forward();
+        let far = eye + 20.0 * camera.forward();
+        assert!((camera.view_projection().project_point(near).z).abs() < eps);
+        assert!((reversed.view_projection().project_point(near).z - 1.0).abs() < eps);
+        assert!((reversed.view_projection().project_point(far).z - DepthConfig::REVERSE_Z.clear).abs() < eps);
+        let (a, b) = (reversed.view_projection().project_point(eye + 2.0 * camera.forward()), reversed.view_projection().project_point(target));
+        assert!(a.z > b.z);
+        assert!(b.x.abs() < eps && b.y.abs() < eps);
+
+        let mut ortho = Camera::orthographic(4.0, 1.0, 1.0, 11.0);
+        ortho.look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero(), Vec3::y_axis());
+        ortho.reverse_z = true;
+        assert!(ortho.view_projection().project_point(Vec3::new(0.0, 0.0, 4.0)).approx_eq(&Vec3::new(0.0, 0.0, 1.0), eps));
+        assert!(ortho.view_projection().project_point(Vec3::new(2.0, 2.0, -6.0)).approx_eq(&Vec3::new(1.0, 1.0, 0.0), eps));
+    }
+
+    #[test]
+    fn mesh_tests() {
+        use super::colour::LinearRgba;
+        use super::mesh::*;
+        let quad = || MeshData::new(
+            vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0)],
+            vec![0, 1, 2, 0, 2, 3],
+        );
+        let mut mesh = quad();
+        assert_eq!(mesh.attributes(), vec![Attribute::Position]);
+        assert_eq!(mesh.layout().array_stride, 12);
+        assert_eq!(mesh.submeshes, vec![Submesh { name: String::new(), indices: 0..6, material: None }]);
+        mesh.validate().unwrap();
+
+        // Attributes keep their shader locations and are packed in location order whatever is missing.
+        mesh.colours = vec![LinearRgba::rgb(1.0, 0.0, 0.0); 4];
+        mesh.compute_normals().unwrap();
+        assert!(mesh.normals.iter().all(|n| n.approx_eq(&Vec3::z_axis(), 1e-6)));
+        let layout = mesh.layout();
+        assert_eq!(layout.array_stride, 12 + 12 + 16);
+        let placed: Vec<_> = layout.attributes.iter().map(|a| (a.shader_location, a.offset, a.format)).collect();
+        assert_eq!(placed, vec![(0, 0, wgpu::VertexFormat::Float3), (2, 12, wgpu::VertexFormat::Float3), (4, 24, wgpu::VertexFormat::Float4)]);
+        assert_eq!(layout.buffer_layout().array_stride, 40);
+        let bytes = mesh.vertex_bytes();
+        assert_eq!(bytes.len(), 4 * 40);
+        let floats: &[f32] = bytemuck::cast_slice(&bytes[40..80]);
+        assert_eq!(floats, &[1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0]);
+
+        // Indices are u16 while every vertex fits, padded to 4 bytes.
+        assert_eq!(mesh.index_format(), wgpu::IndexFormat::Uint16);
+        assert_eq!(mesh.index_bytes().len(), 12);
+        mesh.indices.truncate(3);
+        assert_eq!(bytemuck::cast_slice::<u8, u16>(&mesh.index_bytes()), &[0, 1, 2, 0]);
+        // 0xFFFF is the primitive restart index, so u16 stops one vertex short of it.
+        let mut big = MeshData::new(vec![Vec3::zero(); 65535], vec![0, 1, 65534]);
+        assert_eq!(big.index_format(), wgpu::IndexFormat::Uint16);
+        assert_eq!(bytemuck::cast_slice::<u8, u16>(&big.index_bytes())[..3], [0, 1, 65534]);
+        big.positions.push(Vec3::zero());
+        big.indices.extend_from_slice(&[65535, 1, 0]);
+        assert_eq!(big.index_format(), wgpu::IndexFormat::Uint32);
+        assert_eq!(bytemuck::cast_slice::<u8, u32>(&big.index_bytes()), &[0, 1, 65534, 65535, 1, 0]);
+
+        // Smooth normals average the faces around a vertex by area.
+        let mut corner = MeshData::new(
+            vec![Vec3::zero(), Vec3::x_axis(), Vec3::y_axis(), Vec3::z_axis(), Vec3::new(5.0, 5.0, 5.0)],
+            vec![0, 1, 2, 0, 3, 1],
+        );
+        corner.compute_normals().unwrap();
+        assert!(corner.normals[0].approx_eq(&Vec3::new(0.0, 1.0, 1.0).normalize(), 1e-6));
+        assert!(corner.normals[2].approx_eq(&Vec3::z_axis(), 1e-6));
+        assert!(corner.normals[3].approx_eq(&Vec3::y_axis(), 1e-6));
+        assert_eq!(corner.normals[4], Vec3::z_axis());
+        corner.indices.extend_from_slice(&[4, 2, 5]);
+        assert!(matches!(corner.compute_normals(), Err(super::Error::InvalidMesh(_))));
+
+        let mut parts = quad();
+        parts.submeshes = vec![
+            Submesh { name: "lower".to_string(), indices: 0..3, material: Some(0) },
+            Submesh { name: "upper".to_string(), indices: 3..6, material: Some(1) },
+        ];
+        parts.validate().unwrap();
+        parts.submeshes[1].indices = 3..9;
+        assert!(matches!(parts.validate(), Err(super::Error::InvalidMesh(_))));
+        let mut bad = quad();
+        bad.uvs = vec![Vec2::new(0.0, 0.0); 3];
+        assert_eq!(bad.validate().unwrap_err().to_string(), "invalid mesh: 3 Uv values for 4 positions");
+        bad = quad();
+        bad.indices[4] = 4;
+        assert_eq!(bad.validate().unwrap_err().to_string(), "invalid mesh: index 4 is out of range for 4 vertices");
+        bad.indices.pop();
+        assert!(bad.validate().is_err());
+        assert!(MeshData::new(vec![], vec![]).validate().is_err());
+    }
+
+    #[test]
+    fn obj_tests() {
+        use super::obj::*;
+        use super::colour::LinearRgba;
+        use super::Error;
+        use std::path::Path;
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+        let cube = load_obj(fixtures.join("cube.obj")).unwrap();
+        let names: Vec<&str> = cube.materials.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Wood", "Metal"]);
+        let (wood, metal) = (&cube.materials[0], &cube.materials[1]);
+        assert_eq!(wood.diffuse, LinearRgba::rgb(0.6, 0.4, 0.2));
+        assert_eq!(wood.ambient, LinearRgba::rgb(0.1, 0.1, 0.1));
+        assert_eq!(wood.shininess, 10.0);
+        assert_eq!(wood.diffuse_texture, Some(fixtures.join("textures/wood.png")));
+        assert_eq!(metal.diffuse.a, 0.5);
+        assert_eq!(metal.specular, LinearRgba::rgb(1.0, 1.0, 1.0));
+        assert_eq!(metal.normal_texture, Some(fixtures.join("textures/metal_normal.png")));
+        assert_eq!(metal.diffuse_texture, None);
+
+        // Quads become two triangles and corners only merge when all their attributes match.
+        let mesh = &cube.mesh;
+        mesh.validate().unwrap();
+        assert_eq!((mesh.vertex_count(), mesh.indices.len()), (24, 36));
+        let parts: Vec<_> = mesh.submeshes.iter().map(|s| (s.name.as_str(), s.indices.clone(), s.material)).collect();
+        assert_eq!(parts, vec![("Cube", 0..24, Some(0)), ("Cube", 24..36, Some(1))]);
+        assert_eq!(&mesh.indices[..6], &[0, 1, 2, 0, 2, 3]);
+        assert_eq!(mesh.positions[0], Vec3::new(-0.5, -0.5, 0.5));
+        assert_eq!(mesh.uvs[0], Vec2::new(0.0, 1.0));
+        assert_eq!(mesh.uvs[2], Vec2::new(1.0, 0.0));
+        assert!(mesh.normals[..4].iter().all(|&n| n == Vec3::z_axis()));
+        for triangle in mesh.indices.chunks(3) {
+            let corner = |i: usize| mesh.positions[triangle[i] as usize];
+            let (a, b, c) = (corner(0), corner(1), corner(2));
+            let face = Vec3::cross(b - a, c - a).normalize();
+            assert!(face.approx_eq(&mesh.normals[triangle[0] as usize], 1e-6));
+        }
+
+        let pyramid = load_obj(fixtures.join("pyramid.obj")).unwrap();
+        let mesh = &pyramid.mesh;
+        assert!(pyramid.materials.is_empty());
+        assert_eq!((mesh.vertex_count(), mesh.indices.len()), (5, 18));
+        assert!(mesh.uvs.is_empty() && mesh.colours.is_empty());
+        let parts: Vec<_> = mesh.submeshes.iter().map(|s| (s.name.as_str(), s.indices.clone(), s.material)).collect();
+        assert_eq!(parts, vec![("sides", 0..12, None), ("base", 12..18, None)]);
+        assert!(mesh.normals[0].approx_eq(&Vec3::y_axis(), 1e-6));
+        assert!(mesh.normals.iter().all(|n| (n.length() - 1.0).abs() < 1e-6));
+        assert!(mesh.normals[1].y < 0.0 && mesh.normals[1].x < 0.0 && mesh.normals[1].z > 0.0);
+
+        // Relative indices, continued lines, vertex colours and normals given for only some faces.
+        let source = "v 0 0 0 1 0 0\nv 1 0 0\nv 1 1 0 # comment\nv 0 1 0\nvn 0 0 -1\n\
+                      mtllib missing.mtl\nusemtl nowhere\nf -4//1 -3//1 \\\n -2//1\nf 1 3 4\n";
+        let model = parse_obj(source, Path::new("/nonexistent")).unwrap();
+        let mesh = &model.mesh;
+        assert_eq!(mesh.vertex_count(), 6);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(mesh.submeshes[0].material, None);
+        assert_eq!(mesh.normals[0], -Vec3::z_axis());
+        assert_eq!(mesh.normals[3], Vec3::z_axis());
+        assert_eq!(mesh.colours[0], LinearRgba::rgb(1.0, 0.0, 0.0));
+        assert_eq!(mesh.colours[3], LinearRgba::rgb(1.0, 0.0, 0.0));
+        assert_eq!(mesh.colours[1], LinearRgba::rgb(1.0, 1.0, 1.0));
+
+        let error = |source: &str| parse_obj(source, Path::new("")).unwrap_err().to_string();
+        assert_eq!(error("v 0 0 0\nv 1 0 0\n\nf 1 2\n"), "failed to parse line 4: face has 2 vertices, at least 3 are needed");
+        assert_eq!(error("v 0 0 0\nf 1 1 0\n"), "failed to parse line 2: face corner \"0\" refers to element 0 of 1");
+        assert_eq!(error("v 0 0 0\nf 1 1 -2\n"), "failed to parse line 2: face corner \"-2\" refers to element -2 of 1");
+        assert_eq!(error("v 0 0 0\nf 1/1 1 1\n"), "failed to parse line 2: face corner \"1/1\" refers to element 1 of 0");
+        assert_eq!(error("v 0 zero 0\n"), "failed to parse line 1: \"zero\" is not a number");
+        assert!(matches!(parse_mtl("Kd 1 1 1\n", Path::new("")), Err(Error::ObjParse { line: 1, .. })));
+        assert_eq!(error("usemtl two words\n"), "failed to parse line 1: expected one name, found 2 words");
+        let broken = parse_obj("mtllib broken.mtl\n", &fixtures).unwrap_err();
+        assert_eq!(broken.to_string(), format!("failed to parse {} line 3: \"one\" is not a number", fixtures.join("broken.mtl").display()));
+
+        // Ear clipping keeps every triangle of a concave face facing the same way and inside the face.
+        let concave = load_obj(fixtures.join("concave.obj")).unwrap().mesh;
+        assert_eq!(concave.indices.len(), 12);
+        let mut area = 0.0;
+        for triangle in concave.indices.chunks(3) {
+            let corner = |i: usize| concave.positions[triangle[i] as usize];
+            let n = Vec3::cross(corner(1) - corner(0), corner(2) - corner(0));
+            assert!(n.z > 0.0);
+            area += 0.5 * n.z;
+        }
+        assert!((area - 3.0).abs() < 1e-6);
+
+        let missing = load_obj(fixtures.join("missing.obj")).unwrap_err();
+        assert!(matches!(missing, Error::Io { .. }));
+        assert!(std::error::Error::source(&missing).is_some());
+        assert_eq!(missing.to_string(), format!("failed to read {}", fixtures.join("missing.obj").display()));
+    }
+
+    // With the `simd` feature this checks the SSE2 path against the scalar Mat4 code bit for bit.
+    #[test]
+    fn batch_tests() {
+        let a = Transform4D::from_mat_vec(Mat3::rot(0.7, Vec3::new(1.0, 2.0, 3.0).normalize()), Vec3::new(1.0, 2.0, 3.0));
+        // Every column of `b` has all three components, so each term of the product is exercised.
+        let b = Transform4D::from_mat_vec(Mat3::rot_x(0.4) * Mat3::rot_y(0.3) * Mat3::scale(2.0, 1.0, 0.5), Vec3::new(-4.0, 0.5, 2.0));
+        let (ma, mb) = (Mat4::from(a), Mat4::from(b));
+        let v = Vec3::new(0.3, -1.7, 2.9);
+        assert_eq!(Mat4::from(a * b), ma * mb);
+        assert_eq!(a * v, (ma * Vec4::point(v)).truncate());
+        assert_eq!(a * Vec4::new(0.3, -1.7, 2.9, 0.5), ma * Vec4::new(0.3, -1.7, 2.9, 0.5));
+        assert_eq!(a.mat3() * v, (ma * Vec4::dir(v)).truncate());
+        assert_eq!(Mat4::from(Transform4D::from_mat_vec(a.mat3() * b.mat3(), Vec3::zero())), Mat4::from(Transform4D::from_mat_vec(a.mat3(), Vec3::zero())) * Mat4::from(Transform4D::from_mat_vec(b.mat3(), Vec3::zero())));
+        let h = Vec4::new(1.0, -2.0, 3.0, -4.0);
+        assert_eq!(h + h, Vec4::new(2.0, -4.0, 6.0, -8.0));
+        assert_eq!(h - Vec4::one(), Vec4::new(0.0, -3.0, 2.0, -5.0));
+        assert_eq!(0.5 * h, Vec4::new(0.5, -1.0, 1.5, -2.0));
+
+        let points: Vec<Vec3> = (0..100).map(|i| Vec3::new(i as f32, (i % 7) as f32, -0.5 * i as f32)).collect();
+        let mut out = vec![Vec3::zero(); points.len()];
+        a.transform_points(&points, &mut out);
+        assert!(points.iter().zip(out.iter()).all(|(&p, &o)| o == a * p));
+        a.transform_vectors(&points, &mut out);
+        assert!(points.iter().zip(out.iter()).all(|(&p, &o)| o == a.transform_vector(p)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn batch_length_mismatch() {
+        let points = [Vec3::one(); 4];
+        let mut out = [Vec3::zero(); 3];
+        Transform4D::identity().transform_points(&points, &mut out);
+    }
+
+    #[test]
+    fn layout_tests() {
+        use std::mem::size_of;
+        assert_eq!(size_of::<Vec2>(), 8);
+        assert_eq!(size_of::<Vec3>(), 12);
+        assert_eq!(size_of::<Vec4>(), 16);
+        assert_eq!(size_of::<Quat>(), 16);
+        assert_eq!(size_of::<Mat3>(), 36);
+        assert_eq!(size_of::<Mat4>(), 64);
+        assert_eq!(size_of::<Transform4D>(), 64);
+        assert_eq!(size_of::<Std140Vec3>(), 16);
+        assert_eq!(size_of::<Std140Mat3>(), 48);
+
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(bytemuck::cast::<Vec3, [f32; 3]>(v), [1.0, 2.0, 3.0]);
+        let m = Mat3::from_cols(v, 2.0 * v, 3.0 * v);
+        assert_eq!(bytemuck::cast::<Mat3, [f32; 9]>(m), [1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 3.0, 6.0, 9.0]);
+        let padded = Std140Mat3::from(m);
+        assert_eq!(
+            bytemuck::cast::<Std140Mat3, [f32; 12]>(padded),
+            [1.0, 2.0, 3.0, 0.0, 2.0, 4.0, 6.0, 0.0, 3.0, 6.0, 9.0, 0.0]
+        );
+        assert_eq!(Mat3::from(padded), m);
+        let t = Transform4D::from_mat_vec(Mat3::identity(), v);
+        assert_eq!(&bytemuck::cast::<Transform4D, [f32; 16]>(t)[12..], &[1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(bytemuck::cast_slice::<Vec4, f32>(&[Vec4::w_axis()]), &[0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_tests() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Level {
+            transform: Transform4D,
+            #[serde(with = "trs")]
+            editable: Transform4D,
+            singular: Transform4D,
+            trs: Transform,
+            color: Vec4,
+            uv: Vec2,
+            frame: DTransform4D,
+            projection: Mat4,
+            bounds: Aabb,
+            #[serde(with = "compact")]
+            packed: Transform4D,
+            #[serde(with = "compact")]
+            packed_trs: Transform,
+            #[serde(with = "compact")]
+            packed_projection: Mat4,
+            #[serde(with = "compact")]
+            packed_frame: DTransform4D,
+            #[serde(with = "compact")]
+            packed_rotation: Quat,
+        }
+
+        let eps = 1e-5;
+        let rotation = Quat::rot(0.7, Vec3::new(1.0, 2.0, 3.0).normalize());
+        let trs = Transform::new(Vec3::new(1.0, -2.0, 3.5), rotation, Vec3::new(2.0, 0.5, 1.0));
+        let sheared = Transform4D::from_mat_vec(
+            Mat3::rot_y(0.3) * Mat3::skew(PI / 4.0, Vec3::x_axis(), Vec3::y_axis()) * Mat3::scale(-2.0, 3.0, 1.0),
+            Vec3::one(),
+        );
+        let level = Level {
+            transform: sheared,
+            editable: sheared,
+            singular: Transform4D::from_mat_vec(Mat3::scale(1.0, 0.0, 2.0), Vec3::one()),
+            trs,
+            color: Vec4::new(0.25, 0.5, 0.75, 1.0),
+            uv: Vec2::new(0.5, 1.0),
+            frame: DTransform4D::from(trs.to_transform4d()),
+            projection: Mat4::perspective(PI / 3.0, 1.5, 0.1, 100.0),
+            bounds: Aabb::new(-Vec3::one(), Vec3::one()),
+            packed: sheared,
+            packed_trs: trs,
+            packed_projection: Mat4::perspective(PI / 3.0, 1.5, 0.1, 100.0),
+            packed_frame: DTransform4D::from(sheared),
+            packed_rotation: rotation,
+        };
+
+        // Everything except the TRS form of Transform4D goes through the file unchanged.
+        let check = |back: Level| {
+            assert!(Mat4::from(back.editable).approx_eq(&Mat4::from(level.editable), eps));
+            assert_eq!(Level { editable: level.editable, ..back }, level);
+        };
+        let json = serde_json::to_string(&level).unwrap();
+        check(serde_json::from_str(&json).unwrap());
+        let text = ron::ser::to_string_pretty(&level, ron::ser::PrettyConfig::default()).unwrap();
+        check(ron::de::from_str(&text).unwrap());
+
+        // Readable and compact forms.
+        let t = Transform4D::from_mat_vec(Mat3::identity(), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            serde_json::to_string(&t).unwrap(),
+            r#"{"mat3":{"c0":{"x":1.0,"y":0.0,"z":0.0},"c1":{"x":0.0,"y":1.0,"z":0.0},"c2":{"x":0.0,"y":0.0,"z":1.0}},"translation":{"x":1.0,"y":2.0,"z":3.0}}"#
+        );
+        let mut ser = serde_json::Serializer::new(Vec::new());
+        trs::serialize(&t, &mut ser).unwrap();
+        assert_eq!(
+            String::from_utf8(ser.into_inner()).unwrap(),
+            r#"{"translation":{"x":1.0,"y":2.0,"z":3.0},"rotation":{"x":0.0,"y":0.0,"z":0.0,"w":1.0},"scale":{"x":1.0,"y":1.0,"z":1.0}}"#
+        );
+        assert!(trs::serialize(&level.singular, &mut serde_json::Serializer::new(Vec::new())).is_err());
+        assert!(json.contains(r#""packed_rotation":["#));
+        assert_eq!(ron::ser::to_string(&Vec3::new(1.0, 2.0, 3.0)).unwrap(), "(x:1,y:2,z:3)");
+        let mut ser = serde_json::Serializer::new(Vec::new());
+        compact::serialize(&t, &mut ser).unwrap();
+        assert_eq!(String::from_utf8(ser.into_inner()).unwrap(), "[1.0,0.0,0.0,0.0,1.0,0.0,0.0,0.0,1.0,1.0,2.0,3.0]");
+        let back: Transform4D = compact::deserialize(&mut serde_json::Deserializer::from_str("[1,0,0,0,1,0,0,0,1,1,2,3]")).unwrap();
+        assert_eq!(back, t);
+    }
+
+}
+
+// Randomized checks of the products and inverses against each other and against a plain f64 4x4 multiply.
+#[cfg(test)]
+mod property_tests {
+    use super::math::*;
+    use proptest::prelude::*;
+
+    type M4 = [[f64; 4]; 4];
+
+    fn reference_mul(a: &M4, b: &M4) -> M4 {
+        let mut r = [[0.0; 4]; 4];
+        for c in 0..4 {
+            for row in 0..4 {
+                r[c][row] = (0..4).map(|k| a[k][row] * b[c][k]).sum();
+            }
+        }
+        r
+    }
+
+    fn reference_mul_vec(a: &M4, v: [f64; 4]) -> [f64; 4] {
+        let mut r = [0.0; 4];
+        for (row, value) in r.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * v[k]).sum();
+        }
+        r
+    }
+
+    fn to_m4(e: &[[f32; 4]; 4]) -> M4 {
+        let mut r = [[0.0; 4]; 4];
+        for (rc, ec) in r.iter_mut().zip(e.iter()) {
+            for (x, &y) in rc.iter_mut().zip(ec.iter()) {
+                *x = y as f64;
+            }
+        }
+        r
+    }
+
+    fn mat3_m4(m: Mat3) -> M4 {
+        to_m4(Transform4D::from_mat_vec(m, Vec3::zero()).as_ref())
+    }
+
+    // Largest absolute component, at least 1. Rounding errors of a product grow with the magnitudes of its
+    // factors rather than of the result, which can be tiny after cancellation.
+    fn magnitude(x: &[f32]) -> f64 {
+        x.iter().fold(1.0f64, |m, &v| m.max(v.abs() as f64))
+    }
+
+    // Absolute tolerance for entries up to 1, relative beyond that.
+    fn close(a: &[f64], b: &[f64], eps: f64) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(&a, &b)| (a - b).abs() <= eps * a.abs().max(b.abs()).max(1.0))
+    }
+
+    fn close_m4(a: &M4, b: &M4, eps: f64) -> bool {
+        (0..4).all(|c| close(&a[c], &b[c], eps))
+    }
+
+    fn vec3_f64(v: Vec3) -> [f64; 3] {
+        [v.x as f64, v.y as f64, v.z as f64]
+    }
+
+    fn scalar() -> impl Strategy<Value = f32> {
+        -10.0f32..10.0
+    }
+
+    fn vec3() -> impl Strategy<Value = Vec3> {
+        (scalar(), scalar(), scalar()).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+    }
+
+    fn vec4() -> impl Strategy<Value = Vec4> {
+        (vec3(), scalar()).prop_map(|(v, w)| v.extend(w))
+    }
+
+    fn mat3() -> impl Strategy<Value = Mat3> {
+        (vec3(), vec3(), vec3()).prop_map(|(a, b, c)| Mat3::from_cols(a, b, c))
+    }
+
+    fn transform4d() -> impl Strategy<Value = Transform4D> {
+        (mat3(), vec3()).prop_map(|(m, t)| Transform4D::from_mat_vec(m, t))
+    }
+
+    fn mat4() -> impl Strategy<Value = Mat4> {
+        (vec4(), vec4(), vec4(), vec4()).prop_map(|(a, b, c, d)| Mat4::from_cols(a.into(), b.into(), c.into(), d.into()))
+    }
+
+    fn unit_quat() -> impl Strategy<Value = Quat> {
+        (vec3().prop_filter("axis too short", |a| a.length() > 0.1), -4.0f32..4.0)
+            .prop_map(|(axis, angle)| Quat::rot(angle, axis.normalize()))
+    }
+
+    fn scale() -> impl Strategy<Value = Vec3> {
+        (0.5f32..2.0, 0.5f32..2.0, 0.5f32..2.0).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+    }
+
+    // Well conditioned affine transforms, so inverses and long products stay accurate.
+    fn trs() -> impl Strategy<Value = Transform4D> {
+        (unit_quat(), scale(), -0.5f32..0.5, vec3()).prop_map(|(r, s, shear, t)| {
+            let m = r.to_mat3() * Mat3::skew(shear.atan(), Vec3::x_axis(), Vec3::y_axis()) * Mat3::scale(s.x, s.y, s.z);
+            Transform4D::from_mat_vec(m, t)
+        })
+    }
+
+    fn uniform_transform() -> impl Strategy<Value = Transform> {
+        (vec3(), unit_quat(), 0.5f32..2.0).prop_map(|(t, r, s)| Transform::new(t, r, Vec3::new(s, s, s)))
+    }
+
+    proptest! {
+        #[test]
+        fn transform4d_matches_reference(a in transform4d(), b in transform4d(), v in vec3(), h in vec4()) {
+            let (ra, rb) = (to_m4(a.as_ref()), to_m4(b.as_ref()));
+            let eps = 1e-6 * magnitude(a.components()) * magnitude(b.components()).max(magnitude(v.components())).max(magnitude(h.components()));
+            prop_assert!(close_m4(&to_m4((a * b).as_ref()), &reference_mul(&ra, &rb), eps));
+            let p = reference_mul_vec(&ra, [v.x as f64, v.y as f64, v.z as f64, 1.0]);
+            prop_assert!(close(&vec3_f64(a * v), &p[..3], eps));
+            let d = reference_mul_vec(&ra, [v.x as f64, v.y as f64, v.z as f64, 0.0]);
+            prop_assert!(close(&vec3_f64(a.transform_vector(v)), &d[..3], eps));
+            let r = reference_mul_vec(&ra, [h.x as f64, h.y as f64, h.z as f64, h.w as f64]);
+            let ah = a * h;
+            prop_assert!(close(&[ah.x as f64, ah.y as f64, ah.z as f64, ah.w as f64], &r, eps));
+        }
+
+        #[test]
+        fn mat3_matches_reference(a in mat3(), b in mat3(), v in vec3()) {
+            let eps = 1e-6 * magnitude(a.components()) * magnitude(b.components()).max(magnitude(v.components()));
+            prop_assert!(close_m4(&mat3_m4(a * b), &reference_mul(&mat3_m4(a), &mat3_m4(b)), eps));
+            let r = reference_mul_vec(&mat3_m4(a), [v.x as f64, v.y as f64, v.z as f64, 0.0]);
+            prop_assert!(close(&vec3_f64(a * v), &r[..3], eps));
+        }
+
+        #[test]
+        fn mat4_matches_reference(a in mat4(), b in mat4(), h in vec4()) {
+            let (ra, rb) = (to_m4(a.as_ref()), to_m4(b.as_ref()));
+            let eps = 1e-6 * magnitude(a.components()) * magnitude(b.components()).max(magnitude(h.components()));
+            prop_assert!(close_m4(&to_m4((a * b).as_ref()), &reference_mul(&ra, &rb), eps));
+            let ah = a * h;
+            let r = reference_mul_vec(&ra, [h.x as f64, h.y as f64, h.z as f64, h.w as f64]);
+            prop_assert!(close(&[ah.x as f64, ah.y as f64, ah.z as f64, ah.w as f64], &r, eps));
+        }
+
+        #[test]
+        fn f64_types_match_reference(a in transform4d(), b in transform4d(), v in vec3()) {
+            let (da, db) = (DTransform4D::from(a), DTransform4D::from(b));
+            let reference = reference_mul(da.as_ref(), db.as_ref());
+            prop_assert!(close_m4((da * db).as_ref(), &reference, 1e-12));
+            prop_assert!(close_m4(&mat3_m4((da.mat3() * db.mat3()).as_mat3()), &mat3_m4((da * db).mat3().as_mat3()), 1e-6));
+            let p = reference_mul_vec(da.as_ref(), [v.x as f64, v.y as f64, v.z as f64, 1.0]);
+            let dv = da * DVec3::from(v);
+            prop_assert!(close(&[dv.x, dv.y, dv.z], &p[..3], 1e-12));
+        }
+
+        #[test]
+        fn quat_matches_mat3(a in unit_quat(), b in unit_quat(), v in vec3()) {
+            let reference = reference_mul(&mat3_m4(a.to_mat3()), &mat3_m4(b.to_mat3()));
+            prop_assert!(close_m4(&mat3_m4((a * b).to_mat3()), &reference, 1e-5));
+            prop_assert!(close(&vec3_f64(a * v), &vec3_f64(a.to_mat3() * v), 1e-5));
+            prop_assert!((a * a.inv()).approx_eq(&Quat::identity(), 1e-6) || (a * a.inv()).approx_eq(&-Quat::identity(), 1e-6));
+            prop_assert!(Quat::from_mat3(a.to_mat3()).to_mat3().approx_eq(&a.to_mat3(), 1e-5));
+        }
+
+        #[test]
+        fn transform_matches_reference(a in uniform_transform(), b in uniform_transform(), v in vec3()) {
+            let (ma, mb) = (a.to_transform4d(), b.to_transform4d());
+            let reference = reference_mul(&to_m4(ma.as_ref()), &to_m4(mb.as_ref()));
+            prop_assert!(close_m4(&to_m4((a * b).to_transform4d().as_ref()), &reference, 1e-5));
+            prop_assert!(close(&vec3_f64(a.transform_point(v)), &vec3_f64(ma * v), 1e-5));
+            prop_assert!(close(&vec3_f64(a.inv().transform_point(a.transform_point(v))), &vec3_f64(v), 1e-4));
+        }
+
+        #[test]
+        fn composition_is_associative(a in trs(), b in trs(), c in trs()) {
+            prop_assert!(close_m4(&to_m4(((a * b) * c).as_ref()), &to_m4((a * (b * c)).as_ref()), 1e-5));
+            let (ma, mb, mc) = (a.mat3(), b.mat3(), c.mat3());
+            prop_assert!(close_m4(&mat3_m4((ma * mb) * mc), &mat3_m4(ma * (mb * mc)), 1e-5));
+            let (pa, pb, pc) = (Mat4::from(a), Mat4::from(b), Mat4::from(c));
+            prop_assert!(close_m4(&to_m4(((pa * pb) * pc).as_ref()), &to_m4((pa * (pb * pc)).as_ref()), 1e-5));
+        }
+
+        #[test]
+        fn composition_agrees_with_mat3(a in transform4d(), b in transform4d(), v in vec3()) {
+            let ab = a * b;
+            let (ma, mb) = (magnitude(a.components()), magnitude(b.components()));
+            prop_assert!(close_m4(&mat3_m4(ab.mat3()), &mat3_m4(a.mat3() * b.mat3()), 1e-6 * ma * mb));
+            let t = a.mat3() * b.translation() + a.translation();
+            prop_assert!(close(&vec3_f64(ab.translation()), &vec3_f64(t), 1e-6 * ma * mb));
+            prop_assert!(close(&vec3_f64(ab * v), &vec3_f64(a * (b * v)), 1e-5 * ma * mb * magnitude(v.components())));
+        }
+
+        #[test]
+        fn mat3_decompositions(m in mat3(), a in trs()) {
+            let (u, sigma, v) = m.svd();
+            let scale = sigma.x.max(1.0);
+            prop_assert!((u * Mat3::scale(sigma.x, sigma.y, sigma.z) * v.transpose()).approx_eq(&m, 1e-5 * scale));
+            prop_assert!((u.transpose() * u).approx_eq(&Mat3::identity(), 1e-5));
+            prop_assert!((v.transpose() * v).approx_eq(&Mat3::identity(), 1e-5) && v.det() > 0.0);
+            prop_assert!(sigma.x >= sigma.y && sigma.y >= sigma.z && sigma.z >= 0.0);
+
+            let (r, s) = m.polar();
+            prop_assert!((r * s).approx_eq(&m, 1e-5 * scale));
+            prop_assert!(s.approx_eq(&s.transpose(), 1e-5 * scale));
+            prop_assert!(r.det() * m.det() >= 0.0);
+
+            let sym = m + m.transpose();
+            let (values, vectors) = sym.symmetric_eigen();
+            let diag = Mat3::scale(values.x, values.y, values.z);
+            prop_assert!((vectors * diag * vectors.transpose()).approx_eq(&sym, 1e-5 * sym.c0.length().max(sym.c1.length()).max(sym.c2.length()).max(1.0)));
+            prop_assert!(values.x >= values.y && values.y >= values.z);
+
+            let q = a.mat3().orthonormalize();
+            prop_assert!((q.transpose() * q).approx_eq(&Mat3::identity(), 1e-5));
+            prop_assert!((q.det() - a.det().signum()).abs() < 1e-5);
+        }
+
+        #[test]
+        fn inverse_identities(a in trs(), b in trs(), v in vec3()) {
+            let identity = to_m4(Transform4D::identity().as_ref());
+            prop_assert!(close_m4(&to_m4((a * a.inv()).as_ref()), &identity, 1e-5));
+            prop_assert!(close_m4(&to_m4((a.inv() * a).as_ref()), &identity, 1e-5));
+            prop_assert!(close_m4(&to_m4((a * b).inv().as_ref()), &to_m4((b.inv() * a.inv()).as_ref()), 1e-4));
+            prop_assert!(close_m4(&to_m4(a.inv().inv().as_ref()), &to_m4(a.as_ref()), 1e-4));
+            prop_assert!(close_m4(&mat3_m4(a.mat3().inv()), &mat3_m4(a.inv().mat3()), 1e-5));
+            prop_assert!(close_m4(&to_m4(Mat4::from(a).inv().as_ref()), &to_m4(a.inv().as_ref()), 1e-4));
+            prop_assert!(close(&vec3_f64(a.inv() * (a * v)), &vec3_f64(v), 1e-4));
+            prop_assert!(close(&[a.det() as f64], &[Mat4::from(a).det() as f64], 1e-5));
+            let n = a.transform_normal(Vec3::x_axis());
+            let tangent = a.transform_vector(Vec3::y_axis());
+            prop_assert!(Vec3::dot(n, tangent).abs() <= 1e-4 * n.length() * tangent.length());
+        }
     }
 }
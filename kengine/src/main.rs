@@ -1,151 +1,49 @@
-use winit::{
-    event::*,
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
-    window::Window,
-};
-use wgpu::util::DeviceExt;
-use futures::executor::block_on;
-
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct Vertex {
-    position: [f32; 3],
-    tex_coords: [f32; 2],
-}
-
-impl Vertex {
-    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
-        use std::mem;
-        wgpu::VertexBufferLayout {
-            array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::InputStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float3,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float2,
-                },
-            ]
-        }
-    }
+use winit::event::*;
+use kengine::colour::LinearRgba;
+use kengine::texture::Texture;
+use kengine::math::{Vec2, Vec3};
+use kengine::mesh::{Mesh, MeshData};
+use kengine::depth::DepthConfig;
+use kengine::controller::{CameraController, OrbitController};
+use kengine::{App, Context, Result};
+
+// The happy tree pentagon, textured with the image's top left at the top left.
+fn pentagon() -> MeshData {
+    let mut mesh = MeshData::new(
+        vec![
+            Vec3::new(-0.0868241, 0.49240386, 0.0),
+            Vec3::new(-0.49513406, 0.06958647, 0.0),
+            Vec3::new(-0.21918549, -0.44939706, 0.0),
+            Vec3::new(0.35966998, -0.3473291, 0.0),
+            Vec3::new(0.44147372, 0.2347359, 0.0),
+        ],
+        vec![
+            0, 1, 4,
+            1, 2, 4,
+            2, 3, 4,
+        ],
+    );
+    mesh.uvs = mesh.positions.iter().map(|p| Vec2::new(p.x + 0.5, 0.5 - p.y)).collect();
+    mesh
 }
 
-const VERTICES: &[Vertex] = &[
-    Vertex { position: [-0.0868241, 0.49240386, 0.0], tex_coords: [0.4131759, 0.00759614], },
-    Vertex { position: [-0.49513406, 0.06958647, 0.0], tex_coords: [0.0048659444, 0.43041354], },
-    Vertex { position: [-0.21918549, -0.44939706, 0.0], tex_coords: [0.28081453, 0.949397057], },
-    Vertex { position: [0.35966998, -0.3473291, 0.0], tex_coords: [0.85967, 0.84732911], },
-    Vertex { position: [0.44147372, 0.2347359, 0.0], tex_coords: [0.9414737, 0.2652641], },
-];
-
-const INDICES: &[u16] = &[
-    0, 1, 4,
-    1, 2, 4,
-    2, 3, 4,
-];
-
-struct State {
-    surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    sc_desc: wgpu::SwapChainDescriptor,
-    swap_chain: wgpu::SwapChain,
-    size: winit::dpi::PhysicalSize<u32>,
-    colour: wgpu::Color,
+struct Scene {
     render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    num_vertices: u32,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
+    mesh: Mesh,
     diffuse_bind_group: wgpu::BindGroup,
 }
 
-impl State {
-    async fn new(window: &Window) -> Self {
-        let size = window.inner_size();
-        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
-        let surface = unsafe { instance.create_surface(window) };
-        let adapter = instance.request_adapter(
-            &wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-            },
-        ).await.unwrap();
-        let (device, queue) = adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                features: wgpu::Features::empty(),
-                limits: wgpu::Limits::default(),
-                label: None,
-            },
-            None,
-        ).await.unwrap();
-        let sc_desc = wgpu::SwapChainDescriptor {
-            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-            format: adapter.get_swap_chain_preferred_format(&surface),
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
-        };
-        let swap_chain = device.create_swap_chain(&surface, &sc_desc);
+struct Sample {
+    colour: LinearRgba,
+    scene: Option<Scene>,
+    controller: Option<OrbitController>,
+}
 
+impl Scene {
+    fn new(context: &Context) -> Result<Self> {
+        let device = &context.device;
         let diffuse_bytes = include_bytes!("happy-tree.png");
-        let diffuse_image = image::load_from_memory(diffuse_bytes).unwrap();
-        let diffuse_rgba = diffuse_image.as_rgba8().unwrap();
-        use image::GenericImageView;
-        let dimensions = diffuse_image.dimensions();
-        let texture_size = wgpu::Extent3d {
-            width: dimensions.0,
-            height: dimensions.1,
-            depth: 1,
-        };
-        let diffuse_texture = device.create_texture(
-            &wgpu::TextureDescriptor {
-
-
-                size: texture_size,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-
-
-                usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
-                label: Some("diffuse_texture"),
-            }
-        );
-        queue.write_texture(
-
-            wgpu::TextureCopyView {
-                texture: &diffuse_texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-
-            diffuse_rgba,
-
-            wgpu::TextureDataLayout {
-                offset: 0,
-                bytes_per_row: 4 * dimensions.0,
-                rows_per_image: dimensions.1,
-            },
-            texture_size,
-        );
-        let diffuse_texture_view = diffuse_texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let diffuse_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let diffuse_texture = Texture::from_bytes(device, &context.queue, diffuse_bytes, "diffuse_texture")?;
         let texture_bind_group_layout = device.create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
                 entries: &[
@@ -178,28 +76,24 @@ impl State {
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&diffuse_texture_view),
+                        resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&diffuse_sampler),
+                        resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
                     }
                 ],
                 label: Some("diffuse_bind_group"),
             }
         );
 
-        let colour = wgpu::Color {
-            r: 0.1,
-            g: 0.2,
-            b: 0.3,
-            a: 1.0,
-        };
-        let vs_module = device.create_shader_module(&wgpu::include_spirv!("shader.vert.spv"));
-        let fs_module = device.create_shader_module(&wgpu::include_spirv!("shader.frag.spv"));
+        let mesh = Mesh::new(device, &pentagon(), "pentagon")?;
+
+        let vs_module = context.create_shader_module("shader.vert.spv", include_bytes!("shader.vert.spv"))?;
+        let fs_module = context.create_shader_module("shader.frag.spv", include_bytes!("shader.frag.spv"))?;
         let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[&texture_bind_group_layout],
+            bind_group_layouts: &[&texture_bind_group_layout, &context.camera_binding.layout],
             push_constant_ranges: &[],
         });
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -209,14 +103,14 @@ impl State {
                 module: &vs_module,
                 entry_point: "main",
                 buffers: &[
-                    Vertex::desc(),
+                    mesh.layout.buffer_layout(),
                 ],
             },
             fragment: Some(wgpu::FragmentState {
                 module: &fs_module,
                 entry_point: "main",
                 targets: &[wgpu::ColorTargetState {
-                    format: sc_desc.format,
+                    format: context.format,
                     alpha_blend: wgpu::BlendState::REPLACE,
                     color_blend: wgpu::BlendState::REPLACE,
                     write_mask: wgpu::ColorWrite::ALL,
@@ -230,157 +124,101 @@ impl State {
 
                 polygon_mode: wgpu::PolygonMode::Fill,
             },
-            depth_stencil: None,
+            depth_stencil: Some(context.depth.config.depth_stencil_state()),
             multisample: wgpu::MultisampleState {
                 count: 1,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
         });
-        let vertex_buffer = device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex Buffer"),
-                contents: bytemuck::cast_slice(VERTICES),
-                usage: wgpu::BufferUsage::VERTEX,
-            }
-        );
-        let index_buffer = device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(INDICES),
-                usage: wgpu::BufferUsage::INDEX,
-            }
-        );
-        let num_vertices = VERTICES.len() as u32;
-        let num_indices = INDICES.len() as u32;
 
-        Self {
-            surface,
-            device,
-            queue,
-            sc_desc,
-            swap_chain,
-            size,
-            colour,
+        Ok(Self {
             render_pipeline,
-            vertex_buffer,
-            num_vertices,
-            index_buffer,
-            num_indices,
+            mesh,
             diffuse_bind_group,
-        }
+        })
     }
+}
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        self.size = new_size;
-        self.sc_desc.width = new_size.width;
-        self.sc_desc.height = new_size.height;
-        self.swap_chain = self.device.create_swap_chain(&self.surface, &self.sc_desc);
+impl App for Sample {
+    fn init(&mut self, context: &mut Context) -> Result<()> {
+        context.set_depth_config(DepthConfig::REVERSE_Z);
+        context.camera.look_at(Vec3::new(0.0, 0.0, 2.0), Vec3::zero(), Vec3::y_axis());
+        self.controller = Some(OrbitController::new(&context.camera, Vec3::zero(), 0.005));
+        self.scene = Some(Scene::new(context)?);
+        Ok(())
     }
 
-    fn input(&mut self, event: &WindowEvent) -> bool {
-        match event {
-            WindowEvent::CursorMoved { position, ..} => self.colour = wgpu::Color {
-                r: position.x / self.sc_desc.width as f64,
-                g: position.y / self.sc_desc.height as f64,
-                b: 0.0,
-                a: 1.0,
-            },
-            _ => { }
-        };
+    fn input(&mut self, context: &Context, event: &WindowEvent) -> bool {
+        if let Some(controller) = &mut self.controller {
+            if controller.window_event(event) {
+                return true;
+            }
+        }
+        if let WindowEvent::CursorMoved { position, .. } = event {
+            self.colour = LinearRgba::rgb(
+                (position.x / context.size.width as f64) as f32,
+                (position.y / context.size.height as f64) as f32,
+                0.0,
+            );
+        }
 
         false
     }
 
-    fn update(&mut self) {
+    fn device_event(&mut self, _context: &Context, event: &DeviceEvent) {
+        if let Some(controller) = &mut self.controller {
+            controller.device_event(event);
+        }
+    }
 
+    fn update(&mut self, context: &mut Context, dt: f32) {
+        if let Some(controller) = &mut self.controller {
+            controller.update(&mut context.camera, dt);
+        }
     }
 
-    fn render(&mut self) -> Result<(), wgpu::SwapChainError> {
-        let frame = self
-            .swap_chain
-            .get_current_frame().unwrap().output;
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Render Encoder"),
+    fn render(&mut self, context: &Context, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.colour.into()),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: Some(context.depth.attachment()),
         });
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: &frame.view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.colour),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: None,
-            });
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+        if let Some(scene) = &self.scene {
+            render_pass.set_pipeline(&scene.render_pipeline);
+            render_pass.set_bind_group(0, &scene.diffuse_bind_group, &[]);
+            render_pass.set_bind_group(1, &context.camera_binding.bind_group, &[]);
+            scene.mesh.draw(&mut render_pass);
         }
-        self.queue.submit(std::iter::once(encoder.finish()));
-
-        Ok(())
     }
 }
 
+// `kengine --headless [OUTPUT]` renders a frame without a window and saves it as a PNG.
 fn main() {
-    env_logger::init();
-    let event_loop = EventLoop::new();
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
-    let mut state = block_on(State::new(&window));
-
-    event_loop.run(move |event, _, control_flow| {
-        match event {
-            Event::WindowEvent {
-                ref event,
-                window_id,
-            } if window_id == window.id() => {
-                if !state.input(event) {
-
-                    match event {
-                        WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                        WindowEvent::KeyboardInput { input, .. } => match input {
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
-                                ..
-                            } => *control_flow = ControlFlow::Exit,
-                            _ => {}
-                        },
-                        WindowEvent::Resized(physical_size) => {
-                            state.resize(*physical_size);
-                        }
-                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-
-                            state.resize(**new_inner_size);
-                        }
-                        _ => {}
-                    }
-                }
-            }
-            Event::RedrawRequested(_) => {
-                state.update();
-                match state.render() {
-                    Ok(_) => {}
-
-                    Err(wgpu::SwapChainError::Lost) => state.resize(state.size),
-
-                    Err(wgpu::SwapChainError::OutOfMemory) => *control_flow = ControlFlow::Exit,
-
-                    Err(e) => eprintln!("{:?}", e),
-                }
-            }
-            Event::MainEventsCleared => {
-
+    let sample = Sample {
+        colour: LinearRgba::rgb(0.1, 0.2, 0.3),
+        scene: None,
+        controller: None,
+    };
+    let args: Vec<String> = std::env::args().collect();
+    let result = match args.get(1).map(String::as_str) {
+        Some("--headless") => render_to_png(sample, args.get(2).map_or("frame.png", String::as_str)),
+        _ => kengine::run(sample),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e.report());
+        std::process::exit(1);
+    }
+}
 
-                window.request_redraw();
-            }
-            _ => {}
-        }
-    });
+fn render_to_png(sample: Sample, path: &str) -> Result<()> {
+    let frames = kengine::run_headless(sample, 800, 600, 1)?;
+    kengine::headless::save_png(&frames[0], path)
 }
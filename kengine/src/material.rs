@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+use crate::colour::LinearRgba;
+
+// Surface description in the Phong terms MTL files use. The alpha of `diffuse` is the opacity, texture
+// paths are resolved and ready to load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    pub ambient: LinearRgba,
+    pub diffuse: LinearRgba,
+    pub specular: LinearRgba,
+    pub emissive: LinearRgba,
+    pub shininess: f32,
+    pub diffuse_texture: Option<PathBuf>,
+    pub specular_texture: Option<PathBuf>,
+    pub normal_texture: Option<PathBuf>,
+}
+
+impl Material {
+    // Opaque white, no highlights.
+    pub fn new(name: &str) -> Material {
+        Material {
+            name: name.to_string(),
+            ambient: LinearRgba::rgb(0.0, 0.0, 0.0),
+            diffuse: LinearRgba::rgb(1.0, 1.0, 1.0),
+            specular: LinearRgba::rgb(0.0, 0.0, 0.0),
+            emissive: LinearRgba::rgb(0.0, 0.0, 0.0),
+            shininess: 0.0,
+            diffuse_texture: None,
+            specular_texture: None,
+            normal_texture: None,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+use super::mat3::Mat3;
+use super::mat4::Mat4;
+use super::quat::Quat;
+use super::transform::Transform;
+use super::transform4d::Transform4D;
+use std::slice;
+
+// Component-wise float comparisons. Every component has to pass for two values to be equal, and NaN never
+// equals anything. `epsilon` is an absolute tolerance that also covers values close to zero, where
+// relative and ulps differences blow up.
+pub trait ApproxEq {
+    fn components(&self) -> &[f32];
+
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        all(self, other, |a, b| (a - b).abs() <= epsilon)
+    }
+
+    // Passes when the difference is within `max_relative` times the larger magnitude.
+    fn relative_eq(&self, other: &Self, epsilon: f32, max_relative: f32) -> bool {
+        all(self, other, |a, b| {
+            let diff = (a - b).abs();
+            a == b || diff <= epsilon || diff <= a.abs().max(b.abs()) * max_relative
+        })
+    }
+
+    // Passes when at most `max_ulps` representable floats lie between the two values.
+    fn ulps_eq(&self, other: &Self, epsilon: f32, max_ulps: u32) -> bool {
+        all(self, other, |a, b| {
+            if (a - b).abs() <= epsilon {
+                true
+            } else if a.is_nan() || b.is_nan() || a.is_sign_negative() != b.is_sign_negative() {
+                false
+            } else {
+                let (a, b) = (a.to_bits() as i64, b.to_bits() as i64);
+                (a - b).abs() <= max_ulps as i64
+            }
+        })
+    }
+}
+
+fn all<T: ApproxEq + ?Sized>(a: &T, b: &T, f: impl Fn(f32, f32) -> bool) -> bool {
+    a.components().iter().zip(b.components()).all(|(&a, &b)| f(a, b))
+}
+
+impl ApproxEq for f32 {
+    fn components(&self) -> &[f32] {
+        slice::from_ref(self)
+    }
+}
+
+impl ApproxEq for Vec2 {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
+
+impl ApproxEq for Vec3 {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
+
+impl ApproxEq for Vec4 {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
+
+impl ApproxEq for Quat {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
+
+impl ApproxEq for Mat3 {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
+
+impl ApproxEq for Mat4 {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
+
+impl ApproxEq for Transform4D {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
+
+impl ApproxEq for Transform {
+    fn components(&self) -> &[f32] {
+        bytemuck::cast_slice(slice::from_ref(self))
+    }
+}
@@ -0,0 +1,187 @@
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+use super::mat3::Mat3;
+use super::mat4::Mat4;
+use super::quat::Quat;
+use super::transform::Transform;
+use super::transform4d::Transform4D;
+use super::dvec3::DVec3;
+use super::dmat3::DMat3;
+use super::dtransform4d::DTransform4D;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Flat array forms of the math types for large data where field names would dominate the file, used as
+//
+//     #[serde(with = "kengine::math::compact")]
+//     transform: Transform4D,
+//
+// Matrices are written column by column. Transform4D and DTransform4D leave out the constant bottom row,
+// Transform is translation, rotation (x, y, z, w) and scale.
+
+pub trait Compact: Sized {
+    type Array: Serialize + for<'de> Deserialize<'de>;
+
+    fn to_array(&self) -> Self::Array;
+    fn from_array(a: Self::Array) -> Self;
+}
+
+pub fn serialize<T: Compact, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    value.to_array().serialize(serializer)
+}
+
+pub fn deserialize<'de, T: Compact, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    T::Array::deserialize(deserializer).map(T::from_array)
+}
+
+impl Compact for Vec2 {
+    type Array = [f32; 2];
+
+    fn to_array(&self) -> [f32; 2] {
+        (*self).into()
+    }
+
+    fn from_array(a: [f32; 2]) -> Vec2 {
+        a.into()
+    }
+}
+
+impl Compact for Vec3 {
+    type Array = [f32; 3];
+
+    fn to_array(&self) -> [f32; 3] {
+        (*self).into()
+    }
+
+    fn from_array(a: [f32; 3]) -> Vec3 {
+        a.into()
+    }
+}
+
+impl Compact for Vec4 {
+    type Array = [f32; 4];
+
+    fn to_array(&self) -> [f32; 4] {
+        (*self).into()
+    }
+
+    fn from_array(a: [f32; 4]) -> Vec4 {
+        a.into()
+    }
+}
+
+impl Compact for DVec3 {
+    type Array = [f64; 3];
+
+    fn to_array(&self) -> [f64; 3] {
+        (*self).into()
+    }
+
+    fn from_array(a: [f64; 3]) -> DVec3 {
+        a.into()
+    }
+}
+
+impl Compact for Quat {
+    type Array = [f32; 4];
+
+    fn to_array(&self) -> [f32; 4] {
+        [self.x, self.y, self.z, self.w]
+    }
+
+    fn from_array(a: [f32; 4]) -> Quat {
+        Quat::new(a[0], a[1], a[2], a[3])
+    }
+}
+
+impl Compact for Mat3 {
+    type Array = [f32; 9];
+
+    fn to_array(&self) -> [f32; 9] {
+        let (a, b, c) = (self.c0, self.c1, self.c2);
+        [a.x, a.y, a.z, b.x, b.y, b.z, c.x, c.y, c.z]
+    }
+
+    fn from_array(a: [f32; 9]) -> Mat3 {
+        Mat3::from_cols(
+            Vec3::new(a[0], a[1], a[2]),
+            Vec3::new(a[3], a[4], a[5]),
+            Vec3::new(a[6], a[7], a[8]),
+        )
+    }
+}
+
+impl Compact for DMat3 {
+    type Array = [f64; 9];
+
+    fn to_array(&self) -> [f64; 9] {
+        let (a, b, c) = (self.c0, self.c1, self.c2);
+        [a.x, a.y, a.z, b.x, b.y, b.z, c.x, c.y, c.z]
+    }
+
+    fn from_array(a: [f64; 9]) -> DMat3 {
+        DMat3::from_cols(
+            DVec3::new(a[0], a[1], a[2]),
+            DVec3::new(a[3], a[4], a[5]),
+            DVec3::new(a[6], a[7], a[8]),
+        )
+    }
+}
+
+impl Compact for Mat4 {
+    type Array = [f32; 16];
+
+    fn to_array(&self) -> [f32; 16] {
+        bytemuck::cast(*self.as_ref())
+    }
+
+    fn from_array(a: [f32; 16]) -> Mat4 {
+        let [c0, c1, c2, c3]: [[f32; 4]; 4] = bytemuck::cast(a);
+        Mat4::from_cols(c0, c1, c2, c3)
+    }
+}
+
+impl Compact for Transform4D {
+    type Array = [f32; 12];
+
+    fn to_array(&self) -> [f32; 12] {
+        let (m, t) = (self.mat3().to_array(), self.translation());
+        [m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7], m[8], t.x, t.y, t.z]
+    }
+
+    fn from_array(a: [f32; 12]) -> Transform4D {
+        let m = Mat3::from_array([a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7], a[8]]);
+        Transform4D::from_mat_vec(m, Vec3::new(a[9], a[10], a[11]))
+    }
+}
+
+impl Compact for DTransform4D {
+    type Array = [f64; 12];
+
+    fn to_array(&self) -> [f64; 12] {
+        let (m, t) = (self.mat3().to_array(), self.translation());
+        [m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7], m[8], t.x, t.y, t.z]
+    }
+
+    fn from_array(a: [f64; 12]) -> DTransform4D {
+        let m = DMat3::from_array([a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7], a[8]]);
+        DTransform4D::from_mat_vec(m, DVec3::new(a[9], a[10], a[11]))
+    }
+}
+
+impl Compact for Transform {
+    type Array = [f32; 10];
+
+    fn to_array(&self) -> [f32; 10] {
+        let (t, r, s) = (self.translation, self.rotation, self.scale);
+        [t.x, t.y, t.z, r.x, r.y, r.z, r.w, s.x, s.y, s.z]
+    }
+
+    fn from_array(a: [f32; 10]) -> Transform {
+        Transform::new(
+            Vec3::new(a[0], a[1], a[2]),
+            Quat::new(a[3], a[4], a[5], a[6]),
+            Vec3::new(a[7], a[8], a[9]),
+        )
+    }
+}
@@ -0,0 +1,491 @@
+use super::vec3::Vec3;
+use super::mat3::Mat3;
+use super::quat::Quat;
+use std::cmp::Ordering;
+
+// Parametric curves over Vec3. Every curve is parameterized by t in [0, 1] over its whole length, splines
+// with several segments give each segment an equal share of that range. Values of t outside it are clamped.
+pub trait Curve {
+    fn point(&self, t: f32) -> Vec3;
+    fn derivative(&self, t: f32) -> Vec3;
+    fn second_derivative(&self, t: f32) -> Vec3;
+
+    // Tangent, normal towards the centre of curvature and binormal. Where the curvature vanishes the
+    // normal is an arbitrary perpendicular, so prefer rotation_minimizing_frames for moving cameras.
+    fn frenet_frame(&self, t: f32) -> Frame {
+        let d1 = self.derivative(t);
+        let tangent = d1.normalize();
+        let b = Vec3::cross(d1, self.second_derivative(t));
+        let binormal = if b.length() > CURVATURE_EPS * Vec3::dot(d1, d1) {
+            b.normalize()
+        } else {
+            tangent.orthogonal()
+        };
+        Frame { position: self.point(t), tangent, normal: Vec3::cross(binormal, tangent), binormal }
+    }
+
+    // Parameter of the point on the curve closest to `p`. The curve is sampled `samples` times and the best
+    // sample refined with Newton steps, so `samples` has to be high enough to land near the right bend.
+    fn nearest_t(&self, p: Vec3, samples: usize) -> f32 {
+        let samples = samples.max(2);
+        let dist2 = |t: f32| {
+            let d = self.point(t) - p;
+            Vec3::dot(d, d)
+        };
+        let mut best = (0..samples)
+            .map(|i| i as f32 / (samples - 1) as f32)
+            .fold((0.0, f32::INFINITY), |best, t| {
+                let d = dist2(t);
+                if d < best.1 { (t, d) } else { best }
+            });
+        // Newton on dot(C(t) - p, C'(t)) = 0, keeping only steps that get closer.
+        for _ in 0..NEWTON_STEPS {
+            let (t, d) = best;
+            let offset = self.point(t) - p;
+            let d1 = self.derivative(t);
+            let g = Vec3::dot(offset, d1);
+            let dg = Vec3::dot(d1, d1) + Vec3::dot(offset, self.second_derivative(t));
+            if dg <= 0.0 {
+                break;
+            }
+            let next = (t - g / dg).clamp(0.0, 1.0);
+            let next_d = dist2(next);
+            if next_d >= d {
+                break;
+            }
+            best = (next, next_d);
+        }
+        best.0
+    }
+
+    fn nearest_point(&self, p: Vec3, samples: usize) -> Vec3 {
+        self.point(self.nearest_t(p, samples))
+    }
+}
+
+const CURVATURE_EPS: f32 = 1e-6;
+const NEWTON_STEPS: usize = 8;
+
+// An orthonormal frame moving along a curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    pub position: Vec3,
+    pub tangent: Vec3,
+    pub normal: Vec3,
+    pub binormal: Vec3,
+}
+
+impl Frame {
+    // Columns are tangent, normal and binormal.
+    pub fn to_mat3(&self) -> Mat3 {
+        Mat3::from_cols(self.tangent, self.normal, self.binormal)
+    }
+
+    pub fn to_quat(&self) -> Quat {
+        Quat::from_mat3(self.to_mat3())
+    }
+}
+
+// Polyline through the points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Linear {
+    pub points: Vec<Vec3>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuadraticBezier {
+    pub p0: Vec3,
+    pub p1: Vec3,
+    pub p2: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezier {
+    pub p0: Vec3,
+    pub p1: Vec3,
+    pub p2: Vec3,
+    pub p3: Vec3,
+}
+
+// Passes through every point with tangents given per point. A tangent is the derivative with respect to
+// the segment's own parameter, so its length is in units per segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hermite {
+    pub points: Vec<Vec3>,
+    pub tangents: Vec<Vec3>,
+}
+
+// Uniform Catmull-Rom spline, passes through every point. The end tangents treat the first and last
+// points as repeated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatmullRom {
+    pub points: Vec<Vec3>,
+}
+
+// Uniform cubic B-spline. It is C2 continuous but only approaches its control points, n points make n - 3
+// segments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BSpline {
+    pub points: Vec<Vec3>,
+}
+
+impl Linear {
+    pub fn new(points: Vec<Vec3>) -> Linear {
+        assert!(points.len() >= 2, "a polyline needs at least two points");
+        Linear { points }
+    }
+}
+
+impl QuadraticBezier {
+    pub fn new(p0: Vec3, p1: Vec3, p2: Vec3) -> QuadraticBezier {
+        QuadraticBezier { p0, p1, p2 }
+    }
+}
+
+impl CubicBezier {
+    pub fn new(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3) -> CubicBezier {
+        CubicBezier { p0, p1, p2, p3 }
+    }
+}
+
+impl Hermite {
+    pub fn new(points: Vec<Vec3>, tangents: Vec<Vec3>) -> Hermite {
+        assert!(points.len() >= 2, "a spline needs at least two points");
+        assert_eq!(points.len(), tangents.len(), "every point needs a tangent");
+        Hermite { points, tangents }
+    }
+
+    fn eval(&self, t: f32, basis: fn(f32) -> [f32; 4]) -> (Vec3, usize) {
+        let count = self.points.len() - 1;
+        let (i, u) = segment(t, count);
+        let [h00, h10, h01, h11] = basis(u);
+        let p = h00 * self.points[i] + h10 * self.tangents[i] + h01 * self.points[i + 1] + h11 * self.tangents[i + 1];
+        (p, count)
+    }
+}
+
+impl CatmullRom {
+    pub fn new(points: Vec<Vec3>) -> CatmullRom {
+        assert!(points.len() >= 2, "a spline needs at least two points");
+        CatmullRom { points }
+    }
+
+    fn eval(&self, t: f32, basis: fn(f32) -> [f32; 4]) -> (Vec3, usize) {
+        let n = self.points.len();
+        let (i, u) = segment(t, n - 1);
+        let p = |k: usize| self.points[k.min(n - 1)];
+        let tangent = |k: usize| 0.5 * (p(k + 1) - self.points[k.saturating_sub(1)]);
+        let [h00, h10, h01, h11] = basis(u);
+        (h00 * p(i) + h10 * tangent(i) + h01 * p(i + 1) + h11 * tangent(i + 1), n - 1)
+    }
+}
+
+impl BSpline {
+    pub fn new(points: Vec<Vec3>) -> BSpline {
+        assert!(points.len() >= 4, "a cubic B-spline needs at least four points");
+        BSpline { points }
+    }
+
+    fn eval(&self, t: f32, basis: fn(f32) -> [f32; 4]) -> (Vec3, usize) {
+        let count = self.points.len() - 3;
+        let (i, u) = segment(t, count);
+        let [b0, b1, b2, b3] = basis(u);
+        let p = &self.points[i..i + 4];
+        ((1.0 / 6.0) * (b0 * p[0] + b1 * p[1] + b2 * p[2] + b3 * p[3]), count)
+    }
+}
+
+// Splits the global parameter into a segment index and the parameter within that segment.
+fn segment(t: f32, count: usize) -> (usize, f32) {
+    let x = t.clamp(0.0, 1.0) * count as f32;
+    let i = (x as usize).min(count - 1);
+    (i, x - i as f32)
+}
+
+fn hermite_basis(u: f32) -> [f32; 4] {
+    let (u2, u3) = (u * u, u * u * u);
+    [2.0 * u3 - 3.0 * u2 + 1.0, u3 - 2.0 * u2 + u, -2.0 * u3 + 3.0 * u2, u3 - u2]
+}
+
+fn hermite_basis_d1(u: f32) -> [f32; 4] {
+    let u2 = u * u;
+    [6.0 * u2 - 6.0 * u, 3.0 * u2 - 4.0 * u + 1.0, -6.0 * u2 + 6.0 * u, 3.0 * u2 - 2.0 * u]
+}
+
+fn hermite_basis_d2(u: f32) -> [f32; 4] {
+    [12.0 * u - 6.0, 6.0 * u - 4.0, -12.0 * u + 6.0, 6.0 * u - 2.0]
+}
+
+// Without the common 1/6 factor.
+fn bspline_basis(u: f32) -> [f32; 4] {
+    let (u2, u3, m) = (u * u, u * u * u, 1.0 - u);
+    [m * m * m, 3.0 * u3 - 6.0 * u2 + 4.0, -3.0 * u3 + 3.0 * u2 + 3.0 * u + 1.0, u3]
+}
+
+fn bspline_basis_d1(u: f32) -> [f32; 4] {
+    let (u2, m) = (u * u, 1.0 - u);
+    [-3.0 * m * m, 9.0 * u2 - 12.0 * u, -9.0 * u2 + 6.0 * u + 3.0, 3.0 * u2]
+}
+
+fn bspline_basis_d2(u: f32) -> [f32; 4] {
+    [6.0 * (1.0 - u), 18.0 * u - 12.0, -18.0 * u + 6.0, 6.0 * u]
+}
+
+impl Curve for Linear {
+    fn point(&self, t: f32) -> Vec3 {
+        let (i, u) = segment(t, self.points.len() - 1);
+        Vec3::lerp(self.points[i], self.points[i + 1], u)
+    }
+
+    fn derivative(&self, t: f32) -> Vec3 {
+        let count = self.points.len() - 1;
+        let (i, _) = segment(t, count);
+        count as f32 * (self.points[i + 1] - self.points[i])
+    }
+
+    fn second_derivative(&self, _t: f32) -> Vec3 {
+        Vec3::zero()
+    }
+}
+
+impl Curve for QuadraticBezier {
+    fn point(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0);
+        let m = 1.0 - t;
+        (m * m) * self.p0 + (2.0 * m * t) * self.p1 + (t * t) * self.p2
+    }
+
+    fn derivative(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0);
+        (2.0 * (1.0 - t)) * (self.p1 - self.p0) + (2.0 * t) * (self.p2 - self.p1)
+    }
+
+    fn second_derivative(&self, _t: f32) -> Vec3 {
+        2.0 * (self.p2 - 2.0 * self.p1 + self.p0)
+    }
+}
+
+impl Curve for CubicBezier {
+    fn point(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0);
+        let m = 1.0 - t;
+        (m * m * m) * self.p0 + (3.0 * m * m * t) * self.p1 + (3.0 * m * t * t) * self.p2 + (t * t * t) * self.p3
+    }
+
+    fn derivative(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0);
+        let m = 1.0 - t;
+        (3.0 * m * m) * (self.p1 - self.p0) + (6.0 * m * t) * (self.p2 - self.p1) + (3.0 * t * t) * (self.p3 - self.p2)
+    }
+
+    fn second_derivative(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0);
+        (6.0 * (1.0 - t)) * (self.p2 - 2.0 * self.p1 + self.p0) + (6.0 * t) * (self.p3 - 2.0 * self.p2 + self.p1)
+    }
+}
+
+// Derivatives with respect to the global t pick up a factor of the segment count per order.
+impl Curve for Hermite {
+    fn point(&self, t: f32) -> Vec3 {
+        self.eval(t, hermite_basis).0
+    }
+
+    fn derivative(&self, t: f32) -> Vec3 {
+        let (d, count) = self.eval(t, hermite_basis_d1);
+        count as f32 * d
+    }
+
+    fn second_derivative(&self, t: f32) -> Vec3 {
+        let (d, count) = self.eval(t, hermite_basis_d2);
+        (count * count) as f32 * d
+    }
+}
+
+impl Curve for CatmullRom {
+    fn point(&self, t: f32) -> Vec3 {
+        self.eval(t, hermite_basis).0
+    }
+
+    fn derivative(&self, t: f32) -> Vec3 {
+        let (d, count) = self.eval(t, hermite_basis_d1);
+        count as f32 * d
+    }
+
+    fn second_derivative(&self, t: f32) -> Vec3 {
+        let (d, count) = self.eval(t, hermite_basis_d2);
+        (count * count) as f32 * d
+    }
+}
+
+impl Curve for BSpline {
+    fn point(&self, t: f32) -> Vec3 {
+        self.eval(t, bspline_basis).0
+    }
+
+    fn derivative(&self, t: f32) -> Vec3 {
+        let (d, count) = self.eval(t, bspline_basis_d1);
+        count as f32 * d
+    }
+
+    fn second_derivative(&self, t: f32) -> Vec3 {
+        let (d, count) = self.eval(t, bspline_basis_d2);
+        (count * count) as f32 * d
+    }
+}
+
+// Cumulative chord lengths at evenly spaced parameters, for moving along a curve at constant speed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcLengthTable {
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    pub fn new<C: Curve + ?Sized>(curve: &C, samples: usize) -> ArcLengthTable {
+        let samples = samples.max(1);
+        let mut lengths = Vec::with_capacity(samples + 1);
+        let mut prev = curve.point(0.0);
+        let mut total = 0.0;
+        lengths.push(0.0);
+        for i in 1..=samples {
+            let p = curve.point(i as f32 / samples as f32);
+            total += (p - prev).length();
+            lengths.push(total);
+            prev = p;
+        }
+        ArcLengthTable { lengths }
+    }
+
+    pub fn length(&self) -> f32 {
+        *self.lengths.last().unwrap()
+    }
+
+    pub fn distance_at(&self, t: f32) -> f32 {
+        let (i, u) = segment(t, self.lengths.len() - 1);
+        self.lengths[i] + u * (self.lengths[i + 1] - self.lengths[i])
+    }
+
+    // Inverse of distance_at, interpolating linearly between samples.
+    pub fn t_at_distance(&self, s: f32) -> f32 {
+        let count = self.lengths.len() - 1;
+        let s = s.clamp(0.0, self.length());
+        let i = match self.lengths.binary_search_by(|l| l.partial_cmp(&s).unwrap_or(Ordering::Less)) {
+            Ok(i) => return i as f32 / count as f32,
+            Err(i) => i.max(1) - 1,
+        };
+        let span = self.lengths[i + 1] - self.lengths[i];
+        let u = if span > 0.0 { (s - self.lengths[i]) / span } else { 0.0 };
+        (i as f32 + u) / count as f32
+    }
+
+    // `f` is the fraction of the total length.
+    pub fn t_at_fraction(&self, f: f32) -> f32 {
+        self.t_at_distance(f * self.length())
+    }
+}
+
+// Frames at `count` evenly spaced parameters that twist as little as possible, by the double reflection
+// method (Wang et al. 2008). `up` picks the first normal, it must not be parallel to the start tangent.
+pub fn rotation_minimizing_frames<C: Curve + ?Sized>(curve: &C, count: usize, up: Vec3) -> Vec<Frame> {
+    let count = count.max(2);
+    let at = |i: usize| i as f32 / (count - 1) as f32;
+    let first_tangent = curve.derivative(0.0).normalize();
+    let normal = Vec3::rej(up, first_tangent);
+    let normal = if normal.length() > CURVATURE_EPS { normal.normalize() } else { first_tangent.orthogonal() };
+
+    let mut frames = Vec::with_capacity(count);
+    frames.push(Frame {
+        position: curve.point(0.0),
+        tangent: first_tangent,
+        normal,
+        binormal: Vec3::cross(first_tangent, normal),
+    });
+    for i in 1..count {
+        let prev = frames[i - 1];
+        let position = curve.point(at(i));
+        let tangent = curve.derivative(at(i)).normalize();
+
+        // Reflect across the plane bisecting the two positions, then across the one that maps the
+        // reflected tangent onto the new tangent.
+        let reflect = |v: Vec3, n: Vec3| {
+            let c = Vec3::dot(n, n);
+            if c > 0.0 { v - (2.0 * Vec3::dot(n, v) / c) * n } else { v }
+        };
+        let v1 = position - prev.position;
+        let (r, t) = (reflect(prev.normal, v1), reflect(prev.tangent, v1));
+        let normal = reflect(r, tangent - t).normalize();
+        frames.push(Frame { position, tangent, normal, binormal: Vec3::cross(tangent, normal) });
+    }
+    frames
+}
+
+// Smooth interpolation of orientations through every key with SQUAD, the spherical analogue of a
+// Catmull-Rom spline. Keys are flipped into one hemisphere so each step takes the short way round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuatSpline {
+    keys: Vec<Quat>,
+    controls: Vec<Quat>,
+}
+
+impl QuatSpline {
+    pub fn new(keys: Vec<Quat>) -> QuatSpline {
+        assert!(keys.len() >= 2, "a spline needs at least two keys");
+        let mut aligned: Vec<Quat> = Vec::with_capacity(keys.len());
+        for q in keys {
+            let q = q.normalize();
+            let q = match aligned.last() {
+                Some(&prev) if Quat::dot(prev, q) < 0.0 => -q,
+                _ => q,
+            };
+            aligned.push(q);
+        }
+        let n = aligned.len();
+        let controls = (0..n)
+            .map(|i| {
+                let q = aligned[i];
+                let inv = q.conj();
+                let next = quat_log(inv * aligned[(i + 1).min(n - 1)]);
+                let prev = quat_log(inv * aligned[i.saturating_sub(1)]);
+                q * quat_exp(-0.25 * (next + prev))
+            })
+            .collect();
+        QuatSpline { keys: aligned, controls }
+    }
+
+    pub fn rotation(&self, t: f32) -> Quat {
+        let (i, u) = segment(t, self.keys.len() - 1);
+        let outer = slerp_long(self.keys[i], self.keys[i + 1], u);
+        let inner = slerp_long(self.controls[i], self.controls[i + 1], u);
+        slerp_long(outer, inner, 2.0 * u * (1.0 - u)).normalize()
+    }
+}
+
+// Quat::slerp always takes the short way, which makes SQUAD jump when its inner quaternions drift apart.
+fn slerp_long(a: Quat, b: Quat, t: f32) -> Quat {
+    let cos = Quat::dot(a, b).clamp(-1.0, 1.0);
+    if cos.abs() > 0.9995 {
+        return ((1.0 - t) * a + t * b).normalize();
+    }
+    let theta = cos.acos();
+    let inv_sin = 1.0 / theta.sin();
+    (((1.0 - t) * theta).sin() * inv_sin) * a + ((t * theta).sin() * inv_sin) * b
+}
+
+// Logarithm of a unit quaternion, the rotation vector halved.
+fn quat_log(q: Quat) -> Vec3 {
+    let v = q.vec();
+    let s = v.length();
+    if s < CURVATURE_EPS {
+        v
+    } else {
+        (s.atan2(q.w) / s) * v
+    }
+}
+
+fn quat_exp(v: Vec3) -> Quat {
+    let theta = v.length();
+    if theta < CURVATURE_EPS {
+        Quat::from_vec_scalar(v, 1.0).normalize()
+    } else {
+        Quat::from_vec_scalar((theta.sin() / theta) * v, theta.cos())
+    }
+}
@@ -0,0 +1,238 @@
+use std::ops;
+use super::dvec3::DVec3;
+use super::mat3::Mat3;
+
+const SINGULAR_EPS: f64 = 1e-12;
+
+// f64 counterpart of Mat3.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DMat3 {
+    pub c0: DVec3,
+    pub c1: DVec3,
+    pub c2: DVec3,
+}
+
+impl DMat3 {
+    pub fn from_cols(c0: DVec3, c1: DVec3, c2: DVec3) -> DMat3 {
+        DMat3 { c0, c1, c2, }
+    }
+
+    pub fn row(&self, index: usize) -> DVec3 {
+        DVec3 { x: self.c0[index], y: self.c1[index], z: self.c2[index], }
+    }
+
+    pub fn identity() -> DMat3 {
+        DMat3 {
+            c0: DVec3::x_axis(),
+            c1: DVec3::y_axis(),
+            c2: DVec3::z_axis(),
+        }
+    }
+
+    pub fn zero() -> DMat3 {
+        DMat3 {
+            c0: DVec3::zero(),
+            c1: DVec3::zero(),
+            c2: DVec3::zero(),
+        }
+    }
+
+    pub fn inv(&self) -> DMat3 {
+        let bxc = DVec3::cross(self.c1, self.c2);
+        let cxa = DVec3::cross(self.c2, self.c0);
+        let axb = DVec3::cross(self.c0, self.c1);
+
+        let inv_det = 1.0 / DVec3::dot(axb, self.c2);
+        DMat3 {
+            c0: DVec3::new(bxc.x * inv_det, cxa.x * inv_det, axb.x * inv_det),
+            c1: DVec3::new(bxc.y * inv_det, cxa.y * inv_det, axb.y * inv_det),
+            c2: DVec3::new(bxc.z * inv_det, cxa.z * inv_det, axb.z * inv_det),
+        }
+    }
+
+    pub fn det(&self) -> f64 {
+        DVec3::dot(DVec3::cross(self.c0, self.c1), self.c2)
+    }
+
+    // None when the columns are close to linearly dependent, that is when the determinant is tiny next to
+    // the product of the column lengths, the largest value it could have. Non-finite entries also give None.
+    pub fn try_inv(&self) -> Option<DMat3> {
+        let bound = self.c0.length() * self.c1.length() * self.c2.length();
+        if self.is_finite() && self.det().abs() > SINGULAR_EPS * bound {
+            Some(self.inv())
+        } else {
+            None
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.c0.is_finite() && self.c1.is_finite() && self.c2.is_finite()
+    }
+
+    pub fn rot_x(rads: f64) -> DMat3 {
+        let sin = rads.sin();
+        let cos = rads.cos();
+
+        DMat3 {
+            c0: DVec3::x_axis(),
+            c1: DVec3::new(0.0, cos, sin),
+            c2: DVec3::new(0.0, -sin, cos),
+        }
+    }
+
+    pub fn rot_y(rads: f64) -> DMat3 {
+        let sin = rads.sin();
+        let cos = rads.cos();
+
+        DMat3 {
+            c0: DVec3::new(cos, 0.0, -sin),
+            c1: DVec3::y_axis(),
+            c2: DVec3::new(sin, 0.0, cos),
+        }
+    }
+
+    pub fn rot_z(rads: f64) -> DMat3 {
+        let sin = rads.sin();
+        let cos = rads.cos();
+
+        DMat3 {
+            c0: DVec3::new(cos, sin, 0.0),
+            c1: DVec3::new(-sin, cos, 0.0),
+            c2: DVec3::z_axis(),
+        }
+    }
+
+    pub fn rot(rads: f64, a: DVec3) -> DMat3 {
+        let c = rads.cos();
+        let s = rads.sin();
+        let d = 1.0 - c;
+
+        let x = a.x * d;
+        let y = a.y * d;
+        let z = a.z * d;
+        let axay = x * a.y;
+        let axaz = x * a.z;
+        let ayaz = y * a.z;
+
+        DMat3 {
+            c0: DVec3::new(c + x * a.x, axay + s * a.z, axaz - s * a.y),
+            c1: DVec3::new(axay - s * a.z, c + y * a.y, ayaz + s * a.x),
+            c2: DVec3::new(axaz + s * a.y, ayaz - s * a.x, c + z * a.z),
+        }
+    }
+
+    pub fn reflect(a: DVec3) -> DMat3 {
+        let x = -2.0 * a.x;
+        let y = -2.0 * a.y;
+        let z = -2.0 * a.z;
+        let axay = x * a.y;
+        let axaz = x * a.z;
+        let ayaz = y * a.z;
+
+        DMat3 {
+            c0: DVec3::new(x * a.x + 1.0, axay, axaz),
+            c1: DVec3::new(axay, y * a.y + 1.0, ayaz),
+            c2: DVec3::new(axaz, ayaz, z * a.z + 1.0),
+        }
+    }
+
+    pub fn invol(a: DVec3) -> DMat3 {
+        let x = 2.0 * a.x;
+        let y = 2.0 * a.y;
+        let z = 2.0 * a.z;
+        let axay = x * a.y;
+        let axaz = x * a.z;
+        let ayaz = y * a.z;
+
+        DMat3 {
+           c0: DVec3::new(x * a.x - 1.0, axay, axaz),
+           c1: DVec3::new(axay, y * a.y - 1.0, ayaz),
+           c2: DVec3::new(axaz, ayaz, z * a.z - 1.0),
+        }
+    }
+
+    pub fn scale(sx: f64, sy: f64, sz:f64) -> DMat3 {
+        DMat3 {
+           c0: DVec3::new(sx, 0.0, 0.0),
+           c1: DVec3::new(0.0, sy, 0.0),
+           c2: DVec3::new(0.0, 0.0, sz),
+        }
+    }
+
+    pub fn scale_along_dir(s: f64, a: DVec3) -> DMat3 {
+        let s = s - 1.0;
+        let x = a.x * s;
+        let y = a.y * s;
+        let z = a.z * s;
+        let axay = x * a.y;
+        let axaz = x * a.z;
+        let ayaz = y * a.z;
+
+        DMat3 {
+            c0: DVec3::new(x * a.x + 1.0, axay, axaz),
+            c1: DVec3::new(axay, y * a.y + 1.0, ayaz),
+            c2: DVec3::new(axaz, ayaz, z * a.z + 1.0),
+        }
+    }
+
+    pub fn skew(t: f64, a: DVec3, b: DVec3) -> DMat3 {
+        let t = t.tan();
+        let x = a.x * t;
+        let y = a.y * t;
+        let z = a.z * t;
+
+        DMat3 {
+            c0: DVec3::new(x * b.x + 1.0, y * b.x, z * b.x),
+            c1: DVec3::new(x * b.y, y * b.y + 1.0, z * b.y),
+            c2: DVec3::new(x * b.z, y * b.z, z * b.z + 1.0),
+        }
+    }
+
+    // Rounds every element to the nearest f32.
+    pub fn as_mat3(&self) -> Mat3 {
+        Mat3::from_cols(self.c0.as_vec3(), self.c1.as_vec3(), self.c2.as_vec3())
+    }
+}
+
+impl ops::Add for DMat3 {
+    type Output = Self;
+
+    fn add(self, b: DMat3) -> DMat3 {
+        DMat3::from_cols(self.c0 + b.c0, self.c1 + b.c1, self.c2 + b.c2)
+    }
+}
+
+impl ops::Mul<DVec3> for DMat3 {
+    type Output = DVec3;
+
+    fn mul(self, v: DVec3) -> DVec3 {
+        DVec3 {
+            x: DVec3::dot(self.row(0), v),
+            y: DVec3::dot(self.row(1), v),
+            z: DVec3::dot(self.row(2), v),
+        }
+    }
+}
+
+impl ops::Mul<DMat3> for DMat3 {
+    type Output = Self;
+
+    fn mul(self, m: Self) -> Self {
+        let r0 = self.row(0);
+        let r1 = self.row(1);
+        let r2 = self.row(2);
+        DMat3::from_cols(
+            DVec3::new(DVec3::dot(r0, m.c0), DVec3::dot(r1, m.c0), DVec3::dot(r2, m.c0)),
+            DVec3::new(DVec3::dot(r0, m.c1), DVec3::dot(r1, m.c1), DVec3::dot(r2, m.c1)),
+            DVec3::new(DVec3::dot(r0, m.c2), DVec3::dot(r1, m.c2), DVec3::dot(r2, m.c2)),
+        )
+    }
+}
+
+impl From<Mat3> for DMat3 {
+    fn from(m: Mat3) -> DMat3 {
+        DMat3::from_cols(m.c0.into(), m.c1.into(), m.c2.into())
+    }
+}
@@ -0,0 +1,155 @@
+use super::dmat3::DMat3;
+use super::dvec3::DVec3;
+use super::transform4d::Transform4D;
+use std::ops;
+use std::convert;
+
+// f64 counterpart of Transform4D.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DTransform4D {
+    e: [[f64; 4]; 4],
+}
+
+impl DTransform4D {
+    pub fn from_mat_vec(transform: DMat3, translation: DVec3) -> DTransform4D {
+        let c0 = &transform.c0;
+        let c1 = &transform.c1;
+        let c2 = &transform.c2;
+        DTransform4D {
+            e: [
+                [c0.x, c0.y, c0.z, 0.0],
+                [c1.x, c1.y, c1.z, 0.0],
+                [c2.x, c2.y, c2.z, 0.0],
+                [translation.x, translation.y, translation.z, 1.0],
+            ],
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        n00: f64, n01: f64, n02: f64, n03: f64,
+        n10: f64, n11: f64, n12: f64, n13: f64,
+        n20: f64, n21: f64, n22: f64, n23: f64,
+    ) -> DTransform4D {
+        DTransform4D {
+            e: [
+                [n00, n10, n20, 0.0],
+                [n01, n11, n21, 0.0],
+                [n02, n12, n22, 0.0],
+                [n03, n13, n23, 1.0],
+            ],
+        }
+    }
+
+    pub fn identity() -> DTransform4D {
+        DTransform4D::from_mat_vec(DMat3::identity(), DVec3::zero())
+    }
+
+    pub fn mat3(&self) -> DMat3 {
+        let xyz = |c: [f64; 4]| DVec3::new(c[0], c[1], c[2]);
+        DMat3::from_cols(xyz(self.e[0]), xyz(self.e[1]), xyz(self.e[2]))
+    }
+
+    pub fn translation(&self) -> DVec3 {
+        DVec3::new(self.e[3][0], self.e[3][1], self.e[3][2])
+    }
+
+    // Same as `self * p`: positions are translated.
+    pub fn transform_point(&self, p: DVec3) -> DVec3 {
+        *self * p
+    }
+
+    // Directions and offsets ignore the translation column.
+    pub fn transform_vector(&self, v: DVec3) -> DVec3 {
+        self.mat3() * v
+    }
+
+    // Normals have to stay perpendicular to transformed surfaces, so they go through the inverse transpose
+    // of the upper 3x3 part. The result is not normalized.
+    pub fn transform_normal(&self, n: DVec3) -> DVec3 {
+        let inv = self.mat3().inv();
+        DVec3::new(DVec3::dot(inv.c0, n), DVec3::dot(inv.c1, n), DVec3::dot(inv.c2, n))
+    }
+
+    pub fn det(&self) -> f64 {
+        self.mat3().det()
+    }
+
+    // None when the upper 3x3 part is near-singular, see DMat3::try_inv.
+    pub fn try_inv(&self) -> Option<DTransform4D> {
+        if self.is_finite() && self.mat3().try_inv().is_some() {
+            Some(self.inv())
+        } else {
+            None
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.e.iter().flatten().all(|x| x.is_finite())
+    }
+
+    pub fn inv(&self) -> DTransform4D {
+        let xyz = |c: [f64; 4]| DVec3::new(c[0], c[1], c[2]);
+        let a = xyz(self.e[0]);
+        let b = xyz(self.e[1]);
+        let c = xyz(self.e[2]);
+        let d = xyz(self.e[3]);
+
+        let s = DVec3::cross(a, b);
+        let t = DVec3::cross(c, d);
+
+        let inv_det = 1.0f64 / DVec3::dot(s, c);
+
+        let s = inv_det * s;
+        let t = inv_det * t;
+        let v = inv_det * c;
+
+        let r0 = DVec3::cross(b, v);
+        let r1 = DVec3::cross(v, a);
+
+        DTransform4D::new(
+            r0.x, r0.y, r0.z, -DVec3::dot(b, t),
+            r1.x, r1.y, r1.z, DVec3::dot(a, t),
+            s.x, s.y, s.z, -DVec3::dot(d, s)
+        )
+    }
+
+    // Rounds every element to the nearest f32.
+    pub fn as_transform4d(&self) -> Transform4D {
+        Transform4D::from_mat_vec(self.mat3().as_mat3(), self.translation().as_vec3())
+    }
+}
+
+// Treats `v` as a point. Use transform_vector or transform_normal for directions.
+impl ops::Mul<DVec3> for DTransform4D {
+    type Output = DVec3;
+
+    fn mul(self, v: DVec3) -> DVec3 {
+        DVec3::new(
+            self.e[0][0] * v.x + self.e[1][0] * v.y + self.e[2][0] * v.z + self.e[3][0],
+            self.e[0][1] * v.x + self.e[1][1] * v.y + self.e[2][1] * v.z + self.e[3][1],
+            self.e[0][2] * v.x + self.e[1][2] * v.y + self.e[2][2] * v.z + self.e[3][2],
+        )
+    }
+}
+
+impl ops::Mul<DTransform4D> for DTransform4D {
+    type Output = DTransform4D;
+
+    fn mul(self, t: DTransform4D) -> Self::Output {
+        DTransform4D::from_mat_vec(self.mat3() * t.mat3(), self * t.translation())
+    }
+}
+
+impl convert::AsRef<[[f64; 4]; 4]> for DTransform4D {
+    fn as_ref(&self) -> &[[f64; 4]; 4] {
+        &self.e
+    }
+}
+
+impl From<Transform4D> for DTransform4D {
+    fn from(t: Transform4D) -> DTransform4D {
+        DTransform4D::from_mat_vec(t.mat3().into(), t.translation().into())
+    }
+}
@@ -0,0 +1,152 @@
+use super::vec3::Vec3;
+use std::ops;
+use std::convert;
+
+// f64 counterpart of Vec3 for large worlds and offline tools.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DVec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl DVec3 {
+    pub fn new(x: f64, y: f64, z: f64) -> DVec3 {
+        DVec3 { x, y, z }
+    }
+
+    pub fn dot(a: DVec3, b: DVec3) -> f64 {
+        a.x * b.x + a.y * b.y + a.z * b.z
+    }
+
+    pub fn cross(a: DVec3, b: DVec3) -> DVec3 {
+        DVec3 {
+            x: a.y * b.z - a.z * b.y,
+            y: a.z * b.x - a.x * b.z,
+            z: a.x * b.y - a.y * b.x,
+        }
+    }
+
+    pub fn proj(a: DVec3, b: DVec3) -> DVec3 {
+        DVec3::dot(a, b) / DVec3::dot(b, b) * b
+    }
+
+    pub fn rej(a: DVec3, b: DVec3) -> DVec3 {
+        a - DVec3::dot(a, b) / DVec3::dot(b, b) * b
+    }
+
+    pub fn length(&self) -> f64 {
+        DVec3::dot(*self, *self).sqrt()
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
+    pub fn normalize(&self) -> DVec3 {
+        (1.0 / self.length()) * *self
+    }
+
+    pub fn lerp(a: DVec3, b: DVec3, t: f64) -> DVec3 {
+        a + t * (b - a)
+    }
+
+    pub fn min(a: DVec3, b: DVec3) -> DVec3 {
+        DVec3 { x: a.x.min(b.x), y: a.y.min(b.y), z: a.z.min(b.z) }
+    }
+
+    pub fn max(a: DVec3, b: DVec3) -> DVec3 {
+        DVec3 { x: a.x.max(b.x), y: a.y.max(b.y), z: a.z.max(b.z) }
+    }
+
+    pub fn zero() -> DVec3 {
+        DVec3 { x: 0.0, y: 0.0, z: 0.0, }
+    }
+
+    pub fn one() -> DVec3 {
+        DVec3 { x: 1.0, y: 1.0, z: 1.0, }
+    }
+
+    pub fn x_axis() -> DVec3 {
+        DVec3 { x: 1.0, y: 0.0, z: 0.0, }
+    }
+
+    pub fn y_axis() -> DVec3 {
+        DVec3 { x: 0.0, y: 1.0, z: 0.0, }
+    }
+
+    pub fn z_axis() -> DVec3 {
+        DVec3 { x: 0.0, y: 0.0, z: 1.0, }
+    }
+
+    // Rounds every component to the nearest f32.
+    pub fn as_vec3(&self) -> Vec3 {
+        Vec3::new(self.x as f32, self.y as f32, self.z as f32)
+    }
+}
+
+impl ops::Add for DVec3 {
+    type Output = Self;
+
+    fn add(self, b: Self) -> Self {
+        DVec3 { x: self.x + b.x, y: self.y + b.y, z: self.z + b.z }
+    }
+}
+
+impl ops::Sub for DVec3 {
+    type Output = Self;
+
+    fn sub(self, b: Self) -> Self {
+        DVec3 { x: self.x - b.x, y: self.y - b.y, z: self.z - b.z }
+    }
+}
+
+impl ops::Mul<DVec3> for f64 {
+    type Output = DVec3;
+
+    fn mul(self, b: DVec3) -> DVec3 {
+        DVec3 { x: self * b.x, y: self * b.y, z: self * b.z }
+    }
+}
+
+impl ops::Index<usize> for DVec3 {
+    type Output = f64;
+
+    fn index(&self, i: usize) -> &f64 {
+        &self.as_ref()[i]
+    }
+}
+
+impl ops::Neg for DVec3 {
+    type Output = DVec3;
+
+    fn neg(self) -> DVec3 {
+        DVec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl convert::AsRef<[f64; 3]> for DVec3 {
+    fn as_ref(&self) -> &[f64; 3] {
+        bytemuck::cast_ref(self)
+    }
+}
+
+impl From<[f64; 3]> for DVec3 {
+    fn from(a: [f64; 3]) -> DVec3 {
+        DVec3::new(a[0], a[1], a[2])
+    }
+}
+
+impl From<DVec3> for [f64; 3] {
+    fn from(v: DVec3) -> [f64; 3] {
+        [v.x, v.y, v.z]
+    }
+}
+
+impl From<Vec3> for DVec3 {
+    fn from(v: Vec3) -> DVec3 {
+        DVec3::new(v.x.into(), v.y.into(), v.z.into())
+    }
+}
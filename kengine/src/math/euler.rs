@@ -0,0 +1,148 @@
+use super::vec3::Vec3;
+use super::mat3::Mat3;
+use super::quat::Quat;
+
+// Rotation orders, named by the axes the three angles turn about. The first six are Tait-Bryan orders,
+// the rest are proper Euler orders where the first axis is used again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EulerOrder {
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+    Xyx,
+    Xzx,
+    Yxy,
+    Yzy,
+    Zxz,
+    Zyz,
+}
+
+// Intrinsic rotations: with Xyz the matrix is rot_x(first) * rot_y(second) * rot_z(third), so `first`
+// turns about the fixed x axis and `third` about the z axis already rotated by the other two.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EulerAngles {
+    pub first: f32,
+    pub second: f32,
+    pub third: f32,
+    pub order: EulerOrder,
+}
+
+// Below this cos (Tait-Bryan) or sin (proper) of the middle angle the first and third axes line up.
+const GIMBAL_LOCK_EPS: f32 = 1e-5;
+
+impl EulerOrder {
+    pub fn axes(self) -> [usize; 3] {
+        match self {
+            EulerOrder::Xyz => [0, 1, 2],
+            EulerOrder::Xzy => [0, 2, 1],
+            EulerOrder::Yxz => [1, 0, 2],
+            EulerOrder::Yzx => [1, 2, 0],
+            EulerOrder::Zxy => [2, 0, 1],
+            EulerOrder::Zyx => [2, 1, 0],
+            EulerOrder::Xyx => [0, 1, 0],
+            EulerOrder::Xzx => [0, 2, 0],
+            EulerOrder::Yxy => [1, 0, 1],
+            EulerOrder::Yzy => [1, 2, 1],
+            EulerOrder::Zxz => [2, 0, 2],
+            EulerOrder::Zyz => [2, 1, 2],
+        }
+    }
+
+    pub fn is_proper(self) -> bool {
+        let [i, _, k] = self.axes();
+        i == k
+    }
+}
+
+impl EulerAngles {
+    pub fn new(first: f32, second: f32, third: f32, order: EulerOrder) -> EulerAngles {
+        EulerAngles { first, second, third, order }
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        let [i, j, k] = self.order.axes();
+        axis_rot(i, self.first) * axis_rot(j, self.second) * axis_rot(k, self.third)
+    }
+
+    pub fn to_quat(&self) -> Quat {
+        let [i, j, k] = self.order.axes();
+        Quat::rot(self.first, axis(i)) * Quat::rot(self.second, axis(j)) * Quat::rot(self.third, axis(k))
+    }
+
+    // `m` is expected to be a pure rotation. Angles come out in [-pi, pi], the middle one in [-pi/2, pi/2]
+    // for Tait-Bryan orders and in [0, pi] for proper orders. In gimbal lock only the sum or difference of the outer angles is defined,
+    // so `third` is set to zero.
+    pub fn from_mat3(m: Mat3, order: EulerOrder) -> EulerAngles {
+        // Relabel the axes so the order becomes xyz or xyx. An odd relabeling mirrors space,
+        // which flips the sign of every angle.
+        let [i, j, k] = order.axes();
+        let k = if order.is_proper() { 3 - i - j } else { k };
+        let p = [i, j, k];
+        let e = |r: usize, c: usize| m.row(p[r])[p[c]];
+        let odd = (j + 3 - i) % 3 != 1;
+
+        let (a, b, c) = if order.is_proper() {
+            // Picks the solution with a negative middle angle when it is about to be flipped.
+            let s = if odd { -1.0 } else { 1.0 };
+            let sin_b = (e(0, 1) * e(0, 1) + e(0, 2) * e(0, 2)).sqrt();
+            let b = (s * sin_b).atan2(e(0, 0));
+            if sin_b > GIMBAL_LOCK_EPS {
+                ((s * e(1, 0)).atan2(-s * e(2, 0)), b, (s * e(0, 1)).atan2(s * e(0, 2)))
+            } else {
+                (e(2, 1).atan2(e(1, 1)), b, 0.0)
+            }
+        } else {
+            let cos_b = (e(0, 0) * e(0, 0) + e(0, 1) * e(0, 1)).sqrt();
+            let b = e(0, 2).atan2(cos_b);
+            if cos_b > GIMBAL_LOCK_EPS {
+                ((-e(1, 2)).atan2(e(2, 2)), b, (-e(0, 1)).atan2(e(0, 0)))
+            } else {
+                (e(2, 1).atan2(e(1, 1)), b, 0.0)
+            }
+        };
+
+        if odd {
+            EulerAngles::new(-a, -b, -c, order)
+        } else {
+            EulerAngles::new(a, b, c, order)
+        }
+    }
+
+    // `q` is expected to be a unit quaternion.
+    pub fn from_quat(q: Quat, order: EulerOrder) -> EulerAngles {
+        EulerAngles::from_mat3(q.to_mat3(), order)
+    }
+}
+
+fn axis(index: usize) -> Vec3 {
+    match index {
+        0 => Vec3::x_axis(),
+        1 => Vec3::y_axis(),
+        _ => Vec3::z_axis(),
+    }
+}
+
+fn axis_rot(index: usize, rads: f32) -> Mat3 {
+    match index {
+        0 => Mat3::rot_x(rads),
+        1 => Mat3::rot_y(rads),
+        _ => Mat3::rot_z(rads),
+    }
+}
+
+impl From<EulerAngles> for Mat3 {
+    fn from(e: EulerAngles) -> Mat3 {
+        e.to_mat3()
+    }
+}
+
+impl From<EulerAngles> for Quat {
+    fn from(e: EulerAngles) -> Quat {
+        e.to_quat()
+    }
+}
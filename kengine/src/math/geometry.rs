@@ -0,0 +1,542 @@
+use super::vec3::Vec3;
+use super::mat3::Mat3;
+use super::mat4::Mat4;
+
+// Ray casts return the distance along the ray in units of `dir`, hits behind the origin are ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ray {
+    pub origin: Vec3,
+    pub dir: Vec3,
+}
+
+// Points p with dot(n, p) + d = 0. Signed distances are positive on the side `n` points to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane {
+    pub n: Vec3,
+    pub d: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+// Box with orthonormal local axes stored as the columns of `axes`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Obb {
+    pub center: Vec3,
+    pub axes: Mat3,
+    pub half_extents: Vec3,
+}
+
+// Counter-clockwise winding gives the front face, matching the render pipeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Triangle {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub c: Vec3,
+}
+
+// All points within `radius` of the segment ab.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capsule {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub radius: f32,
+}
+
+// Planes are left, right, bottom, top, near, far with normals pointing inside. Frustums taken from a reverse-Z
+// projection have near and far the other way round, see from_mat4.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, dir: Vec3) -> Ray {
+        Ray { origin, dir }
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + t * self.dir
+    }
+
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let denom = Vec3::dot(plane.n, self.dir);
+        if denom == 0.0 {
+            return None;
+        }
+        let t = -plane.dist(self.origin) / denom;
+        if t >= 0.0 { Some(t) } else { None }
+    }
+
+    #[allow(clippy::suspicious_operation_groupings)]
+    pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<f32> {
+        let m = self.origin - sphere.center;
+        let a = Vec3::dot(self.dir, self.dir);
+        let b = Vec3::dot(m, self.dir);
+        let c = Vec3::dot(m, m) - sphere.radius * sphere.radius;
+        if c > 0.0 && b > 0.0 {
+            return None;
+        }
+        let disc = b * b - a * c;
+        if disc < 0.0 {
+            return None;
+        }
+        let t = (-b - disc.sqrt()) / a;
+        Some(t.max(0.0))
+    }
+
+    // Slab test. Starting inside the box is a hit at t = 0.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::INFINITY;
+        for i in 0..3 {
+            let o = self.origin[i];
+            let d = self.dir[i];
+            if d.abs() < f32::EPSILON {
+                if o < aabb.min[i] || o > aabb.max[i] {
+                    return None;
+                }
+            } else {
+                let inv_d = 1.0 / d;
+                let t0 = (aabb.min[i] - o) * inv_d;
+                let t1 = (aabb.max[i] - o) * inv_d;
+                t_min = t_min.max(t0.min(t1));
+                t_max = t_max.min(t0.max(t1));
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+        Some(t_min)
+    }
+
+    pub fn intersect_obb(&self, obb: &Obb) -> Option<f32> {
+        let to_local = obb.axes.inv();
+        let local = Ray::new(to_local * (self.origin - obb.center), to_local * self.dir);
+        local.intersect_aabb(&Aabb::new(-obb.half_extents, obb.half_extents))
+    }
+
+    // Moller-Trumbore, hits both faces.
+    pub fn intersect_triangle(&self, tri: &Triangle) -> Option<f32> {
+        let e1 = tri.b - tri.a;
+        let e2 = tri.c - tri.a;
+        let p = Vec3::cross(self.dir, e2);
+        let det = Vec3::dot(e1, p);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin - tri.a;
+        let u = Vec3::dot(s, p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = Vec3::cross(s, e1);
+        let v = Vec3::dot(self.dir, q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = Vec3::dot(e2, q) * inv_det;
+        if t >= 0.0 { Some(t) } else { None }
+    }
+}
+
+impl Plane {
+    pub fn new(n: Vec3, d: f32) -> Plane {
+        Plane { n, d }
+    }
+
+    pub fn from_point_normal(p: Vec3, n: Vec3) -> Plane {
+        let n = n.normalize();
+        Plane { n, d: -Vec3::dot(n, p) }
+    }
+
+    // The normal faces the side from which a, b, c appear counter-clockwise.
+    pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Plane {
+        Plane::from_point_normal(a, Vec3::cross(b - a, c - a))
+    }
+
+    pub fn normalize(&self) -> Plane {
+        let inv_len = 1.0 / self.n.length();
+        Plane { n: inv_len * self.n, d: inv_len * self.d }
+    }
+
+    pub fn dist(&self, p: Vec3) -> f32 {
+        Vec3::dot(self.n, p) + self.d
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        p - self.dist(p) * self.n
+    }
+}
+
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32) -> Sphere {
+        Sphere { center, radius }
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        let d = p - self.center;
+        Vec3::dot(d, d) <= self.radius * self.radius
+    }
+
+    // Negative inside the sphere.
+    pub fn dist(&self, p: Vec3) -> f32 {
+        (p - self.center).length() - self.radius
+    }
+
+    pub fn intersects_sphere(&self, s: &Sphere) -> bool {
+        let d = s.center - self.center;
+        let r = self.radius + s.radius;
+        Vec3::dot(d, d) <= r * r
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        aabb.intersects_sphere(self)
+    }
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Aabb {
+        Aabb { min, max }
+    }
+
+    pub fn from_points(points: &[Vec3]) -> Aabb {
+        let mut aabb = Aabb::new(f32::INFINITY * Vec3::one(), f32::NEG_INFINITY * Vec3::one());
+        for &p in points {
+            aabb.min = Vec3::min(aabb.min, p);
+            aabb.max = Vec3::max(aabb.max, p);
+        }
+        aabb
+    }
+
+    pub fn center(&self) -> Vec3 {
+        0.5 * (self.min + self.max)
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        0.5 * (self.max - self.min)
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x
+            && p.y >= self.min.y && p.y <= self.max.y
+            && p.z >= self.min.z && p.z <= self.max.z
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        Vec3::min(Vec3::max(p, self.min), self.max)
+    }
+
+    // Zero inside the box.
+    pub fn dist(&self, p: Vec3) -> f32 {
+        (p - self.closest_point(p)).length()
+    }
+
+    pub fn intersects_aabb(&self, b: &Aabb) -> bool {
+        self.min.x <= b.max.x && self.max.x >= b.min.x
+            && self.min.y <= b.max.y && self.max.y >= b.min.y
+            && self.min.z <= b.max.z && self.max.z >= b.min.z
+    }
+
+    pub fn intersects_sphere(&self, s: &Sphere) -> bool {
+        let d = s.center - self.closest_point(s.center);
+        Vec3::dot(d, d) <= s.radius * s.radius
+    }
+}
+
+impl Obb {
+    pub fn new(center: Vec3, axes: Mat3, half_extents: Vec3) -> Obb {
+        Obb { center, axes, half_extents }
+    }
+
+    pub fn from_aabb(aabb: &Aabb) -> Obb {
+        Obb::new(aabb.center(), Mat3::identity(), aabb.half_extents())
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let d = p - self.center;
+        let axes = [self.axes.c0, self.axes.c1, self.axes.c2];
+        let mut q = self.center;
+        for (i, &axis) in axes.iter().enumerate() {
+            let e = self.half_extents[i];
+            q = q + Vec3::dot(d, axis).clamp(-e, e) * axis;
+        }
+        q
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        let d = p - self.center;
+        let axes = [self.axes.c0, self.axes.c1, self.axes.c2];
+        axes.iter().enumerate().all(|(i, &axis)| Vec3::dot(d, axis).abs() <= self.half_extents[i])
+    }
+
+    pub fn dist(&self, p: Vec3) -> f32 {
+        (p - self.closest_point(p)).length()
+    }
+
+    pub fn intersects_sphere(&self, s: &Sphere) -> bool {
+        let d = s.center - self.closest_point(s.center);
+        Vec3::dot(d, d) <= s.radius * s.radius
+    }
+
+    // Separating axis test over the 15 candidate axes.
+    pub fn intersects_obb(&self, b: &Obb) -> bool {
+        let eps = 1e-6;
+        let a_axes = [self.axes.c0, self.axes.c1, self.axes.c2];
+        let b_axes = [b.axes.c0, b.axes.c1, b.axes.c2];
+        let row = |a: Vec3| [Vec3::dot(a, b_axes[0]), Vec3::dot(a, b_axes[1]), Vec3::dot(a, b_axes[2])];
+        let r = [row(a_axes[0]), row(a_axes[1]), row(a_axes[2])];
+        let mut abs_r = [[0.0f32; 3]; 3];
+        for (abs_row, r_row) in abs_r.iter_mut().zip(r.iter()) {
+            for (abs_value, value) in abs_row.iter_mut().zip(r_row.iter()) {
+                // Parallel edges give zero cross products, the epsilon keeps those axes from false positives.
+                *abs_value = value.abs() + eps;
+            }
+        }
+        let d = b.center - self.center;
+        let t = [Vec3::dot(d, a_axes[0]), Vec3::dot(d, a_axes[1]), Vec3::dot(d, a_axes[2])];
+        let ea = self.half_extents;
+        let eb = b.half_extents;
+
+        for i in 0..3 {
+            let ra = ea[i];
+            let rb = eb[0] * abs_r[i][0] + eb[1] * abs_r[i][1] + eb[2] * abs_r[i][2];
+            if t[i].abs() > ra + rb {
+                return false;
+            }
+        }
+        for j in 0..3 {
+            let ra = ea[0] * abs_r[0][j] + ea[1] * abs_r[1][j] + ea[2] * abs_r[2][j];
+            let rb = eb[j];
+            if (t[0] * r[0][j] + t[1] * r[1][j] + t[2] * r[2][j]).abs() > ra + rb {
+                return false;
+            }
+        }
+        for i in 0..3 {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            for j in 0..3 {
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let ra = ea[i1] * abs_r[i2][j] + ea[i2] * abs_r[i1][j];
+                let rb = eb[j1] * abs_r[i][j2] + eb[j2] * abs_r[i][j1];
+                if (t[i2] * r[i1][j] - t[i1] * r[i2][j]).abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl Triangle {
+    pub fn new(a: Vec3, b: Vec3, c: Vec3) -> Triangle {
+        Triangle { a, b, c }
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        Vec3::cross(self.b - self.a, self.c - self.a).normalize()
+    }
+
+    pub fn plane(&self) -> Plane {
+        Plane::from_points(self.a, self.b, self.c)
+    }
+
+    // Walks the Voronoi regions of the vertices and edges before falling back to the face.
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let ab = b - a;
+        let ac = c - a;
+        let ap = p - a;
+        let d1 = Vec3::dot(ab, ap);
+        let d2 = Vec3::dot(ac, ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return a;
+        }
+
+        let bp = p - b;
+        let d3 = Vec3::dot(ab, bp);
+        let d4 = Vec3::dot(ac, bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            return a + (d1 / (d1 - d3)) * ab;
+        }
+
+        let cp = p - c;
+        let d5 = Vec3::dot(ab, cp);
+        let d6 = Vec3::dot(ac, cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            return a + (d2 / (d2 - d6)) * ac;
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            return b + ((d4 - d3) / ((d4 - d3) + (d5 - d6))) * (c - b);
+        }
+
+        let denom = 1.0 / (va + vb + vc);
+        a + (vb * denom) * ab + (vc * denom) * ac
+    }
+
+    pub fn dist(&self, p: Vec3) -> f32 {
+        (p - self.closest_point(p)).length()
+    }
+}
+
+impl Capsule {
+    pub fn new(a: Vec3, b: Vec3, radius: f32) -> Capsule {
+        Capsule { a, b, radius }
+    }
+
+    // Negative inside the capsule.
+    pub fn dist(&self, p: Vec3) -> f32 {
+        (p - closest_point_on_segment(p, self.a, self.b)).length() - self.radius
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        self.dist(p) <= 0.0
+    }
+
+    pub fn intersects_sphere(&self, s: &Sphere) -> bool {
+        let d = s.center - closest_point_on_segment(s.center, self.a, self.b);
+        let r = self.radius + s.radius;
+        Vec3::dot(d, d) <= r * r
+    }
+
+    pub fn intersects_capsule(&self, c: &Capsule) -> bool {
+        let (p, q) = closest_points_segment_segment(self.a, self.b, c.a, c.b);
+        let d = q - p;
+        let r = self.radius + c.radius;
+        Vec3::dot(d, d) <= r * r
+    }
+}
+
+impl Frustum {
+    pub fn new(planes: [Plane; 6]) -> Frustum {
+        Frustum { planes }
+    }
+
+    // Extracts the planes of a view-projection matrix with 0..1 clip depth. The fifth plane is depth 0 and the
+    // sixth depth 1, which makes them far and near for reverse-Z. An infinite projection has no far plane, its
+    // row has a zero normal and is replaced by a plane every point lies in front of.
+    pub fn from_mat4(m: &Mat4) -> Frustum {
+        let row = |i: usize| {
+            let r = m.row(i);
+            (Vec3::new(r[0], r[1], r[2]), r[3])
+        };
+        let (r0, w0) = row(0);
+        let (r1, w1) = row(1);
+        let (r2, w2) = row(2);
+        let (r3, w3) = row(3);
+        let plane = |n: Vec3, d: f32| {
+            if n.length() > f32::EPSILON * d.abs() {
+                Plane::new(n, d).normalize()
+            } else {
+                Plane::new(Vec3::zero(), if d >= 0.0 { 1.0 } else { -1.0 })
+            }
+        };
+
+        Frustum {
+            planes: [
+                plane(r3 + r0, w3 + w0),
+                plane(r3 - r0, w3 - w0),
+                plane(r3 + r1, w3 + w1),
+                plane(r3 - r1, w3 - w1),
+                plane(r2, w2),
+                plane(r3 - r2, w3 - w2),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        self.planes.iter().all(|plane| plane.dist(p) >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, s: &Sphere) -> bool {
+        self.planes.iter().all(|plane| plane.dist(s.center) >= -s.radius)
+    }
+
+    // Conservative: boxes near the frustum corners may be reported as intersecting.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let positive = Vec3::new(
+                if plane.n.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if plane.n.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if plane.n.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+            );
+            plane.dist(positive) >= 0.0
+        })
+    }
+}
+
+pub fn closest_point_on_segment(p: Vec3, a: Vec3, b: Vec3) -> Vec3 {
+    let ab = b - a;
+    let len2 = Vec3::dot(ab, ab);
+    if len2 == 0.0 {
+        return a;
+    }
+    let t = (Vec3::dot(p - a, ab) / len2).clamp(0.0, 1.0);
+    a + t * ab
+}
+
+// Closest points between the segments p1q1 and p2q2, returned in that order.
+#[allow(clippy::suspicious_operation_groupings)]
+pub fn closest_points_segment_segment(p1: Vec3, q1: Vec3, p2: Vec3, q2: Vec3) -> (Vec3, Vec3) {
+    let eps = 1e-12;
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = Vec3::dot(d1, d1);
+    let e = Vec3::dot(d2, d2);
+    let f = Vec3::dot(d2, r);
+
+    if a <= eps && e <= eps {
+        return (p1, p2);
+    }
+    let (s, t) = if a <= eps {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = Vec3::dot(d1, r);
+        if e <= eps {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = Vec3::dot(d1, d2);
+            let denom = a * e - b * b;
+            let s = if denom != 0.0 { ((b * f - c * e) / denom).clamp(0.0, 1.0) } else { 0.0 };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (p1 + s * d1, p2 + t * d2)
+}
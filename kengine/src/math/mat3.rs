@@ -1,7 +1,15 @@
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use super::simd;
 use std::ops;
 use super::vec3::Vec3;
+use super::vec4::Vec4;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+const SINGULAR_EPS: f32 = 1e-6;
+const JACOBI_SWEEPS: usize = 32;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mat3 {
     pub c0: Vec3,
     pub c1: Vec3,
@@ -46,6 +54,25 @@ impl Mat3 {
         }
     }
 
+    pub fn det(&self) -> f32 {
+        Vec3::dot(Vec3::cross(self.c0, self.c1), self.c2)
+    }
+
+    // None when the columns are close to linearly dependent, that is when the determinant is tiny next to
+    // the product of the column lengths, the largest value it could have. Non-finite entries also give None.
+    pub fn try_inv(&self) -> Option<Mat3> {
+        let bound = self.c0.length() * self.c1.length() * self.c2.length();
+        if self.is_finite() && self.det().abs() > SINGULAR_EPS * bound {
+            Some(self.inv())
+        } else {
+            None
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.c0.is_finite() && self.c1.is_finite() && self.c2.is_finite()
+    }
+
     pub fn rot_x(rads: f32) -> Mat3 {
         let sin = rads.sin();
         let cos = rads.cos();
@@ -164,6 +191,98 @@ impl Mat3 {
             c2: Vec3::new(x * b.z, y * b.z, z * b.z + 1.0),
         }
     }
+
+    pub fn transpose(&self) -> Mat3 {
+        Mat3::from_cols(self.row(0), self.row(1), self.row(2))
+    }
+
+    pub fn trace(&self) -> f32 {
+        self.c0.x + self.c1.y + self.c2.z
+    }
+
+    // Gram-Schmidt: keeps the direction of c0, then makes c1 and c2 perpendicular to the columns before them.
+    // Repairs the drift of long rotation products. The columns are expected to be linearly independent.
+    pub fn orthonormalize(&self) -> Mat3 {
+        let c0 = self.c0.normalize();
+        let c1 = Vec3::rej(self.c1, c0).normalize();
+        let c2 = (self.c2 - Vec3::dot(self.c2, c0) * c0 - Vec3::dot(self.c2, c1) * c1).normalize();
+        Mat3::from_cols(c0, c1, c2)
+    }
+
+    // Eigenvalues and eigenvectors (as columns) of a symmetric matrix, by cyclic Jacobi rotations.
+    // Eigenvalues are sorted in decreasing order, the eigenvectors form a rotation.
+    pub fn symmetric_eigen(&self) -> (Vec3, Mat3) {
+        let mut a: [[f32; 3]; 3] = bytemuck::cast(*self);
+        let mut v: [[f32; 3]; 3] = bytemuck::cast(Mat3::identity());
+
+        for _ in 0..JACOBI_SWEEPS {
+            let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+            let diag = a[0][0] * a[0][0] + a[1][1] * a[1][1] + a[2][2] * a[2][2];
+            if off <= f32::EPSILON * f32::EPSILON * diag || off == 0.0 {
+                break;
+            }
+            for &(p, q) in [(0, 1), (0, 2), (1, 2)].iter() {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                // Rotation in the pq plane that zeroes a[p][q], see Numerical Recipes 11.1.
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                let rot = |x: f32, y: f32| (c * x - s * y, s * x + c * y);
+                for row in a.iter_mut() {
+                    let (x, y) = rot(row[p], row[q]);
+                    row[p] = x;
+                    row[q] = y;
+                }
+                let (rp, rq) = (a[p], a[q]);
+                for (k, (&x, &y)) in rp.iter().zip(rq.iter()).enumerate() {
+                    let (x, y) = rot(x, y);
+                    a[p][k] = x;
+                    a[q][k] = y;
+                }
+                // v holds the eigenvectors in its columns, v[c] is the c-th of them.
+                let (x, y) = (Vec3::from(v[p]), Vec3::from(v[q]));
+                v[p] = (c * x - s * y).into();
+                v[q] = (s * x + c * y).into();
+            }
+        }
+
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| a[j][j].partial_cmp(&a[i][i]).unwrap_or(std::cmp::Ordering::Equal));
+        let [i, j, k] = order;
+        let values = Vec3::new(a[i][i], a[j][j], a[k][k]);
+        let (e0, e1) = (Vec3::from(v[i]), Vec3::from(v[j]));
+        (values, Mat3::from_cols(e0, e1, Vec3::cross(e0, e1)))
+    }
+
+    // Returns (u, sigma, v) with self = u * diag(sigma) * v^T. u and v are orthogonal, v is a rotation and
+    // the singular values are non-negative and decreasing. A reflection in `self` ends up in u.
+    pub fn svd(&self) -> (Mat3, Vec3, Mat3) {
+        let (values, v) = (self.transpose() * *self).symmetric_eigen();
+        let sigma = |x: f32| x.max(0.0).sqrt();
+        let (s0, s1) = (sigma(values.x), sigma(values.y));
+
+        // Columns of u are self * v_i / sigma_i, filled in with perpendicular vectors where sigma_i vanishes.
+        let tiny = f32::EPSILON * s0;
+        let u0 = if s0 > 0.0 { (*self * v.c0).normalize() } else { Vec3::x_axis() };
+        let u1 = Vec3::rej(*self * v.c1, u0);
+        let u1 = if s1 > tiny { u1.normalize() } else { u0.orthogonal() };
+        let u2 = Vec3::cross(u0, u1);
+        let s2 = Vec3::dot(u2, *self * v.c2);
+        let (u2, s2) = if s2 < 0.0 { (-u2, -s2) } else { (u2, s2) };
+
+        (Mat3::from_cols(u0, u1, u2), Vec3::new(s0, s1, s2), v)
+    }
+
+    // Splits self into r * s, where r is orthogonal (a rotation when det > 0) and s is symmetric positive
+    // semi-definite, i.e. the rotation closest to self and the stretch applied before it.
+    pub fn polar(&self) -> (Mat3, Mat3) {
+        let (u, sigma, v) = self.svd();
+        let vt = v.transpose();
+        (u * vt, v * Mat3::scale(sigma.x, sigma.y, sigma.z) * vt)
+    }
 }
 
 impl ops::Add for Mat3 {
@@ -178,25 +297,49 @@ impl ops::Mul<Vec3> for Mat3 {
     type Output = Vec3;
 
     fn mul(self, v: Vec3) -> Vec3 {
-        Vec3 {
-            x: Vec3::dot(self.row(0), v),
-            y: Vec3::dot(self.row(1), v),
-            z: Vec3::dot(self.row(2), v),
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::mat3_mul_vec3(&self, v)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            Vec3 {
+                x: Vec3::dot(self.row(0), v),
+                y: Vec3::dot(self.row(1), v),
+                z: Vec3::dot(self.row(2), v),
+            }
         }
     }
 }
 
+// Transforms the xyz part and keeps w, so points and directions both pass through unchanged in kind.
+impl ops::Mul<Vec4> for Mat3 {
+    type Output = Vec4;
+
+    fn mul(self, v: Vec4) -> Vec4 {
+        let xyz = self * v.truncate();
+        Vec4::new(xyz.x, xyz.y, xyz.z, v.w)
+    }
+}
+
 impl ops::Mul<Mat3> for Mat3 {
     type Output = Self;
 
     fn mul(self, m: Self) -> Self {
-        let r0 = self.row(0);
-        let r1 = self.row(1);
-        let r2 = self.row(2);
-        Mat3::from_cols(
-            Vec3::new(Vec3::dot(r0, m.c0), Vec3::dot(r1, m.c0), Vec3::dot(r2, m.c0)),
-            Vec3::new(Vec3::dot(r0, m.c1), Vec3::dot(r1, m.c1), Vec3::dot(r2, m.c1)),
-            Vec3::new(Vec3::dot(r0, m.c2), Vec3::dot(r1, m.c2), Vec3::dot(r2, m.c2)),
-        )
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::mat3_mul_mat3(&self, &m)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            let r0 = self.row(0);
+            let r1 = self.row(1);
+            let r2 = self.row(2);
+            Mat3::from_cols(
+                Vec3::new(Vec3::dot(r0, m.c0), Vec3::dot(r1, m.c0), Vec3::dot(r2, m.c0)),
+                Vec3::new(Vec3::dot(r0, m.c1), Vec3::dot(r1, m.c1), Vec3::dot(r2, m.c1)),
+                Vec3::new(Vec3::dot(r0, m.c2), Vec3::dot(r1, m.c2), Vec3::dot(r2, m.c2)),
+            )
+        }
     }
 }
@@ -0,0 +1,241 @@
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+use super::transform4d::Transform4D;
+use std::ops;
+use std::convert;
+
+const SINGULAR_EPS: f32 = 1e-6;
+
+// General 4x4 matrix, stored column-major like Transform4D but without the fixed (0, 0, 0, 1) bottom row.
+// Projections follow the wgpu conventions: right-handed view space looking down -z and clip space depth in 0..1.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Mat4 {
+    e: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    pub fn from_cols(c0: [f32; 4], c1: [f32; 4], c2: [f32; 4], c3: [f32; 4]) -> Mat4 {
+        Mat4 { e: [c0, c1, c2, c3] }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        n00: f32, n01: f32, n02: f32, n03: f32,
+        n10: f32, n11: f32, n12: f32, n13: f32,
+        n20: f32, n21: f32, n22: f32, n23: f32,
+        n30: f32, n31: f32, n32: f32, n33: f32,
+    ) -> Mat4 {
+        Mat4 {
+            e: [
+                [n00, n10, n20, n30],
+                [n01, n11, n21, n31],
+                [n02, n12, n22, n32],
+                [n03, n13, n23, n33],
+            ],
+        }
+    }
+
+    pub fn identity() -> Mat4 {
+        Mat4::new(
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        )
+    }
+
+    pub fn zero() -> Mat4 {
+        Mat4 { e: [[0.0; 4]; 4] }
+    }
+
+    pub fn col(&self, index: usize) -> [f32; 4] {
+        self.e[index]
+    }
+
+    pub fn row(&self, index: usize) -> [f32; 4] {
+        [self.e[0][index], self.e[1][index], self.e[2][index], self.e[3][index]]
+    }
+
+    pub fn perspective(fovy: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let g = 1.0 / (fovy * 0.5).tan();
+        let k = far / (near - far);
+
+        Mat4::new(
+            g / aspect, 0.0, 0.0, 0.0,
+            0.0, g, 0.0, 0.0,
+            0.0, 0.0, k, near * k,
+            0.0, 0.0, -1.0, 0.0,
+        )
+    }
+
+    // Maps near to depth 1 and far to depth 0, which spreads float precision evenly over the range.
+    pub fn perspective_reverse_z(fovy: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let g = 1.0 / (fovy * 0.5).tan();
+        let k = near / (far - near);
+
+        Mat4::new(
+            g / aspect, 0.0, 0.0, 0.0,
+            0.0, g, 0.0, 0.0,
+            0.0, 0.0, k, far * k,
+            0.0, 0.0, -1.0, 0.0,
+        )
+    }
+
+    pub fn perspective_infinite_reverse_z(fovy: f32, aspect: f32, near: f32) -> Mat4 {
+        let g = 1.0 / (fovy * 0.5).tan();
+
+        Mat4::new(
+            g / aspect, 0.0, 0.0, 0.0,
+            0.0, g, 0.0, 0.0,
+            0.0, 0.0, 0.0, near,
+            0.0, 0.0, -1.0, 0.0,
+        )
+    }
+
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+        let w_inv = 1.0 / (right - left);
+        let h_inv = 1.0 / (top - bottom);
+        let d_inv = 1.0 / (near - far);
+
+        Mat4::new(
+            2.0 * w_inv, 0.0, 0.0, -(right + left) * w_inv,
+            0.0, 2.0 * h_inv, 0.0, -(top + bottom) * h_inv,
+            0.0, 0.0, d_inv, near * d_inv,
+            0.0, 0.0, 0.0, 1.0,
+        )
+    }
+
+    // View matrix for a camera at `eye` looking at `target`.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+        let f = (target - eye).normalize();
+        let s = Vec3::cross(f, up).normalize();
+        let u = Vec3::cross(s, f);
+
+        Mat4::new(
+            s.x, s.y, s.z, -Vec3::dot(s, eye),
+            u.x, u.y, u.z, -Vec3::dot(u, eye),
+            -f.x, -f.y, -f.z, Vec3::dot(f, eye),
+            0.0, 0.0, 0.0, 1.0,
+        )
+    }
+
+    pub fn transpose(&self) -> Mat4 {
+        Mat4::from_cols(self.row(0), self.row(1), self.row(2), self.row(3))
+    }
+
+    pub fn det(&self) -> f32 {
+        let (a, b, c, d) = self.xyz_cols();
+        let [x, y, z, w] = self.row(3);
+
+        let s = Vec3::cross(a, b);
+        let t = Vec3::cross(c, d);
+        let u = y * a - x * b;
+        let v = w * c - z * d;
+
+        Vec3::dot(s, v) + Vec3::dot(t, u)
+    }
+
+    pub fn inv(&self) -> Mat4 {
+        let (a, b, c, d) = self.xyz_cols();
+        let [x, y, z, w] = self.row(3);
+
+        let s = Vec3::cross(a, b);
+        let t = Vec3::cross(c, d);
+        let u = y * a - x * b;
+        let v = w * c - z * d;
+
+        let inv_det = 1.0 / (Vec3::dot(s, v) + Vec3::dot(t, u));
+        let s = inv_det * s;
+        let t = inv_det * t;
+        let u = inv_det * u;
+        let v = inv_det * v;
+
+        let r0 = Vec3::cross(b, v) + y * t;
+        let r1 = Vec3::cross(v, a) - x * t;
+        let r2 = Vec3::cross(d, u) + w * s;
+        let r3 = Vec3::cross(u, c) - z * s;
+
+        Mat4::new(
+            r0.x, r0.y, r0.z, -Vec3::dot(b, t),
+            r1.x, r1.y, r1.z, Vec3::dot(a, t),
+            r2.x, r2.y, r2.z, -Vec3::dot(d, s),
+            r3.x, r3.y, r3.z, Vec3::dot(c, s),
+        )
+    }
+
+    // Same test as Mat3::try_inv, against the product of the four column lengths.
+    pub fn try_inv(&self) -> Option<Mat4> {
+        let length = |c: [f32; 4]| (c[0] * c[0] + c[1] * c[1] + c[2] * c[2] + c[3] * c[3]).sqrt();
+        let bound = self.e.iter().map(|&c| length(c)).product::<f32>();
+        if self.is_finite() && self.det().abs() > SINGULAR_EPS * bound {
+            Some(self.inv())
+        } else {
+            None
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.e.iter().flatten().all(|x| x.is_finite())
+    }
+
+    // Applies the matrix to the point `v` and performs the perspective divide.
+    pub fn project_point(&self, v: Vec3) -> Vec3 {
+        let e = &self.e;
+        let w = e[0][3] * v.x + e[1][3] * v.y + e[2][3] * v.z + e[3][3];
+        let inv_w = 1.0 / w;
+        Vec3::new(
+            (e[0][0] * v.x + e[1][0] * v.y + e[2][0] * v.z + e[3][0]) * inv_w,
+            (e[0][1] * v.x + e[1][1] * v.y + e[2][1] * v.z + e[3][1]) * inv_w,
+            (e[0][2] * v.x + e[1][2] * v.y + e[2][2] * v.z + e[3][2]) * inv_w,
+        )
+    }
+
+    fn xyz_cols(&self) -> (Vec3, Vec3, Vec3, Vec3) {
+        let xyz = |c: [f32; 4]| Vec3::new(c[0], c[1], c[2]);
+        (xyz(self.e[0]), xyz(self.e[1]), xyz(self.e[2]), xyz(self.e[3]))
+    }
+}
+
+impl From<Transform4D> for Mat4 {
+    fn from(t: Transform4D) -> Mat4 {
+        Mat4 { e: *t.as_ref() }
+    }
+}
+
+impl ops::Mul<Mat4> for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, m: Mat4) -> Mat4 {
+        let mut e = [[0.0; 4]; 4];
+        for (c, col) in e.iter_mut().enumerate() {
+            for (r, value) in col.iter_mut().enumerate() {
+                *value = self.e[0][r] * m.e[c][0]
+                    + self.e[1][r] * m.e[c][1]
+                    + self.e[2][r] * m.e[c][2]
+                    + self.e[3][r] * m.e[c][3];
+            }
+        }
+        Mat4 { e }
+    }
+}
+
+impl ops::Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    fn mul(self, v: Vec4) -> Vec4 {
+        let e = &self.e;
+        Vec4::new(
+            e[0][0] * v.x + e[1][0] * v.y + e[2][0] * v.z + e[3][0] * v.w,
+            e[0][1] * v.x + e[1][1] * v.y + e[2][1] * v.z + e[3][1] * v.w,
+            e[0][2] * v.x + e[1][2] * v.y + e[2][2] * v.z + e[3][2] * v.w,
+            e[0][3] * v.x + e[1][3] * v.y + e[2][3] * v.z + e[3][3] * v.w,
+        )
+    }
+}
+
+impl convert::AsRef<[[f32; 4]; 4]> for Mat4 {
+    fn as_ref(&self) -> &[[f32; 4]; 4] {
+        &self.e
+    }
+}
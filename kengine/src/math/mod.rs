@@ -1,7 +1,45 @@
+pub mod vec2;
 pub mod vec3;
+pub mod vec4;
 pub mod mat3;
 pub mod transform4d;
+pub mod dvec3;
+pub mod dmat3;
+pub mod dtransform4d;
+pub mod mat4;
+pub mod quat;
+pub mod euler;
+pub mod transform;
+pub mod std140;
+pub mod geometry;
+pub mod approx;
+pub mod random;
+pub mod noise;
+pub mod curves;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "serde")]
+pub mod compact;
+#[cfg(feature = "serde")]
+pub mod trs;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+pub mod simd;
 
+pub use vec2::*;
 pub use vec3::*;
+pub use vec4::*;
 pub use mat3::*;
 pub use transform4d::*;
+pub use dvec3::*;
+pub use dmat3::*;
+pub use dtransform4d::*;
+pub use mat4::*;
+pub use quat::*;
+pub use euler::*;
+pub use transform::*;
+pub use std140::*;
+pub use geometry::*;
+pub use approx::*;
+pub use random::*;
+pub use noise::*;
+pub use curves::*;
@@ -0,0 +1,334 @@
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+
+// Coherent noise for procedural content. Lattice values and gradients come from hashing the integer cell
+// coordinates with the seed, so there are no permutation tables, no period and the same seed always gives
+// the same field. Perlin, simplex and value noise return roughly [-1, 1] and are zero-mean, Worley returns
+// the distance to the nearest feature point in cell units, which stays below about 1.2.
+pub trait Noise {
+    fn sample2(&self, p: Vec2) -> f32;
+    fn sample3(&self, p: Vec3) -> f32;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Perlin {
+    pub seed: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Simplex {
+    pub seed: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueNoise {
+    pub seed: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Worley {
+    pub seed: u32,
+}
+
+// Fractal Brownian motion: `octaves` layers of `noise`, each `lacunarity` times the frequency and `gain`
+// times the amplitude of the last. The sum is normalised so it keeps the range of the base noise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fbm<N> {
+    pub noise: N,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+// Ridged multifractal: like Fbm but every octave is folded to (1 - |n|)^2, which turns the zero crossings
+// into sharp crests. Returns [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ridged<N> {
+    pub noise: N,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+const SIMPLEX_2D_SCALE: f32 = 70.0;
+const SIMPLEX_3D_SCALE: f32 = 32.0;
+
+impl Perlin {
+    pub fn new(seed: u32) -> Perlin {
+        Perlin { seed }
+    }
+}
+
+impl Simplex {
+    pub fn new(seed: u32) -> Simplex {
+        Simplex { seed }
+    }
+}
+
+impl ValueNoise {
+    pub fn new(seed: u32) -> ValueNoise {
+        ValueNoise { seed }
+    }
+}
+
+impl Worley {
+    pub fn new(seed: u32) -> Worley {
+        Worley { seed }
+    }
+}
+
+impl<N: Noise> Fbm<N> {
+    pub fn new(noise: N, octaves: u32) -> Fbm<N> {
+        Fbm { noise, octaves, lacunarity: 2.0, gain: 0.5 }
+    }
+}
+
+impl<N: Noise> Ridged<N> {
+    pub fn new(noise: N, octaves: u32) -> Ridged<N> {
+        Ridged { noise, octaves, lacunarity: 2.0, gain: 0.5 }
+    }
+}
+
+impl Noise for Perlin {
+    fn sample2(&self, p: Vec2) -> f32 {
+        let (x0, y0) = (p.x.floor(), p.y.floor());
+        let (ix, iy) = (x0 as i32, y0 as i32);
+        let (x, y) = (p.x - x0, p.y - y0);
+        let g = |i: i32, j: i32| grad2(hash(self.seed, ix + i, iy + j, 0), x - i as f32, y - j as f32);
+        let (u, v) = (fade(x), fade(y));
+        lerp(lerp(g(0, 0), g(1, 0), u), lerp(g(0, 1), g(1, 1), u), v)
+    }
+
+    fn sample3(&self, p: Vec3) -> f32 {
+        let (x0, y0, z0) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (ix, iy, iz) = (x0 as i32, y0 as i32, z0 as i32);
+        let (x, y, z) = (p.x - x0, p.y - y0, p.z - z0);
+        let g = |i: i32, j: i32, k: i32| {
+            grad3(hash(self.seed, ix + i, iy + j, iz + k), x - i as f32, y - j as f32, z - k as f32)
+        };
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let front = lerp(lerp(g(0, 0, 0), g(1, 0, 0), u), lerp(g(0, 1, 0), g(1, 1, 0), u), v);
+        let back = lerp(lerp(g(0, 0, 1), g(1, 0, 1), u), lerp(g(0, 1, 1), g(1, 1, 1), u), v);
+        lerp(front, back, w)
+    }
+}
+
+// After Stefan Gustavson, "Simplex noise demystified" (2005).
+impl Noise for Simplex {
+    fn sample2(&self, p: Vec2) -> f32 {
+        let f2 = 0.5 * (3.0f32.sqrt() - 1.0);
+        let g2 = (3.0 - 3.0f32.sqrt()) / 6.0;
+        let s = (p.x + p.y) * f2;
+        let (i, j) = ((p.x + s).floor(), (p.y + s).floor());
+        let t = (i + j) * g2;
+        let (x0, y0) = (p.x - (i - t), p.y - (j - t));
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let corners = [
+            (0, 0, x0, y0),
+            (i1, j1, x0 - i1 as f32 + g2, y0 - j1 as f32 + g2),
+            (1, 1, x0 - 1.0 + 2.0 * g2, y0 - 1.0 + 2.0 * g2),
+        ];
+        let (i, j) = (i as i32, j as i32);
+        let sum: f32 = corners
+            .iter()
+            .map(|&(di, dj, x, y)| {
+                let t = 0.5 - x * x - y * y;
+                if t > 0.0 {
+                    t * t * t * t * grad2(hash(self.seed, i + di, j + dj, 0), x, y)
+                } else {
+                    0.0
+                }
+            })
+            .sum();
+        SIMPLEX_2D_SCALE * sum
+    }
+
+    fn sample3(&self, p: Vec3) -> f32 {
+        let (f3, g3) = (1.0 / 3.0, 1.0 / 6.0);
+        let s = (p.x + p.y + p.z) * f3;
+        let (i, j, k) = ((p.x + s).floor(), (p.y + s).floor(), (p.z + s).floor());
+        let t = (i + j + k) * g3;
+        let (x0, y0, z0) = (p.x - (i - t), p.y - (j - t), p.z - (k - t));
+        // The two middle corners of the simplex, found by ranking the offsets.
+        let (a, b) = if x0 >= y0 {
+            if y0 >= z0 {
+                ((1, 0, 0), (1, 1, 0))
+            } else if x0 >= z0 {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if y0 < z0 {
+            ((0, 0, 1), (0, 1, 1))
+        } else if x0 < z0 {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+        let (i, j, k) = (i as i32, j as i32, k as i32);
+        let corner = |(di, dj, dk): (i32, i32, i32), n: f32| {
+            let (x, y, z) = (x0 - di as f32 + n * g3, y0 - dj as f32 + n * g3, z0 - dk as f32 + n * g3);
+            let t = 0.6 - x * x - y * y - z * z;
+            if t > 0.0 {
+                t * t * t * t * grad3(hash(self.seed, i + di, j + dj, k + dk), x, y, z)
+            } else {
+                0.0
+            }
+        };
+        SIMPLEX_3D_SCALE * (corner((0, 0, 0), 0.0) + corner(a, 1.0) + corner(b, 2.0) + corner((1, 1, 1), 3.0))
+    }
+}
+
+impl Noise for ValueNoise {
+    fn sample2(&self, p: Vec2) -> f32 {
+        let (x0, y0) = (p.x.floor(), p.y.floor());
+        let (ix, iy) = (x0 as i32, y0 as i32);
+        let v = |i: i32, j: i32| signed(hash(self.seed, ix + i, iy + j, 0));
+        let (u, w) = (fade(p.x - x0), fade(p.y - y0));
+        lerp(lerp(v(0, 0), v(1, 0), u), lerp(v(0, 1), v(1, 1), u), w)
+    }
+
+    fn sample3(&self, p: Vec3) -> f32 {
+        let (x0, y0, z0) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (ix, iy, iz) = (x0 as i32, y0 as i32, z0 as i32);
+        let v = |i: i32, j: i32, k: i32| signed(hash(self.seed, ix + i, iy + j, iz + k));
+        let (u, w, s) = (fade(p.x - x0), fade(p.y - y0), fade(p.z - z0));
+        let front = lerp(lerp(v(0, 0, 0), v(1, 0, 0), u), lerp(v(0, 1, 0), v(1, 1, 0), u), w);
+        let back = lerp(lerp(v(0, 0, 1), v(1, 0, 1), u), lerp(v(0, 1, 1), v(1, 1, 1), u), w);
+        lerp(front, back, s)
+    }
+}
+
+// One feature point jittered inside every cell, the nearest of them lies in the cell or one of its
+// neighbours.
+impl Noise for Worley {
+    fn sample2(&self, p: Vec2) -> f32 {
+        let (ix, iy) = (p.x.floor() as i32, p.y.floor() as i32);
+        let mut nearest = f32::MAX;
+        for j in iy - 1..=iy + 1 {
+            for i in ix - 1..=ix + 1 {
+                let h = hash(self.seed, i, j, 0);
+                let feature = Vec2::new(i as f32 + unit(h), j as f32 + unit(mix(h ^ 0x68e3_1da4)));
+                nearest = nearest.min((feature - p).length());
+            }
+        }
+        nearest
+    }
+
+    fn sample3(&self, p: Vec3) -> f32 {
+        let (ix, iy, iz) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        let mut nearest = f32::MAX;
+        for k in iz - 1..=iz + 1 {
+            for j in iy - 1..=iy + 1 {
+                for i in ix - 1..=ix + 1 {
+                    let h = hash(self.seed, i, j, k);
+                    let feature = Vec3::new(
+                        i as f32 + unit(h),
+                        j as f32 + unit(mix(h ^ 0x68e3_1da4)),
+                        k as f32 + unit(mix(h ^ 0xb529_7a4d)),
+                    );
+                    nearest = nearest.min((feature - p).length());
+                }
+            }
+        }
+        nearest
+    }
+}
+
+impl<N: Noise> Noise for Fbm<N> {
+    fn sample2(&self, p: Vec2) -> f32 {
+        octaves(self.octaves, self.lacunarity, self.gain, |f| self.noise.sample2(f * p))
+    }
+
+    fn sample3(&self, p: Vec3) -> f32 {
+        octaves(self.octaves, self.lacunarity, self.gain, |f| self.noise.sample3(f * p))
+    }
+}
+
+impl<N: Noise> Noise for Ridged<N> {
+    fn sample2(&self, p: Vec2) -> f32 {
+        octaves(self.octaves, self.lacunarity, self.gain, |f| ridge(self.noise.sample2(f * p)))
+    }
+
+    fn sample3(&self, p: Vec3) -> f32 {
+        octaves(self.octaves, self.lacunarity, self.gain, |f| ridge(self.noise.sample3(f * p)))
+    }
+}
+
+// Weighted sum of `layer` at increasing frequencies, divided by the total weight.
+fn octaves(count: u32, lacunarity: f32, gain: f32, layer: impl Fn(f32) -> f32) -> f32 {
+    let (mut sum, mut total) = (0.0, 0.0);
+    let (mut frequency, mut amplitude) = (1.0, 1.0);
+    for _ in 0..count {
+        sum += amplitude * layer(frequency);
+        total += amplitude;
+        frequency *= lacunarity;
+        amplitude *= gain;
+    }
+    if total > 0.0 {
+        sum / total
+    } else {
+        0.0
+    }
+}
+
+fn ridge(n: f32) -> f32 {
+    let r = 1.0 - n.abs();
+    r * r
+}
+
+fn hash(seed: u32, x: i32, y: i32, z: i32) -> u32 {
+    mix(seed ^ (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841) ^ (z as u32).wrapping_mul(0xcb1a_b31f))
+}
+
+// The MurmurHash3 finaliser.
+fn mix(mut h: u32) -> u32 {
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
+
+fn unit(h: u32) -> f32 {
+    (h >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+}
+
+fn signed(h: u32) -> f32 {
+    2.0 * unit(h) - 1.0
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (6.0 * t - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
+
+fn grad2(h: u32, x: f32, y: f32) -> f32 {
+    match h & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+// The twelve cube edge directions of Perlin's improved noise, four of them twice to fill sixteen slots.
+fn grad3(h: u32, x: f32, y: f32, z: f32) -> f32 {
+    let h = h & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
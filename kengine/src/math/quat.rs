@@ -0,0 +1,212 @@
+use std::ops;
+use super::vec3::Vec3;
+use super::mat3::Mat3;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Quat {
+        Quat { x, y, z, w }
+    }
+
+    pub fn from_vec_scalar(v: Vec3, s: f32) -> Quat {
+        Quat { x: v.x, y: v.y, z: v.z, w: s }
+    }
+
+    pub fn identity() -> Quat {
+        Quat { x: 0.0, y: 0.0, z: 0.0, w: 1.0, }
+    }
+
+    // `a` is expected to be a unit vector, the same as for Mat3::rot.
+    pub fn rot(rads: f32, a: Vec3) -> Quat {
+        let half = rads * 0.5;
+        Quat::from_vec_scalar(half.sin() * a, half.cos())
+    }
+
+    pub fn rot_x(rads: f32) -> Quat {
+        Quat::rot(rads, Vec3::x_axis())
+    }
+
+    pub fn rot_y(rads: f32) -> Quat {
+        Quat::rot(rads, Vec3::y_axis())
+    }
+
+    pub fn rot_z(rads: f32) -> Quat {
+        Quat::rot(rads, Vec3::z_axis())
+    }
+
+    // `m` is expected to be a pure rotation.
+    pub fn from_mat3(m: Mat3) -> Quat {
+        let m00 = m.c0.x;
+        let m11 = m.c1.y;
+        let m22 = m.c2.z;
+        let sum = m00 + m11 + m22;
+
+        if sum > 0.0 {
+            let w = (sum + 1.0).sqrt() * 0.5;
+            let f = 0.25 / w;
+            Quat::new((m.c1.z - m.c2.y) * f, (m.c2.x - m.c0.z) * f, (m.c0.y - m.c1.x) * f, w)
+        } else if m00 > m11 && m00 > m22 {
+            let x = (m00 - m11 - m22 + 1.0).sqrt() * 0.5;
+            let f = 0.25 / x;
+            Quat::new(x, (m.c0.y + m.c1.x) * f, (m.c2.x + m.c0.z) * f, (m.c1.z - m.c2.y) * f)
+        } else if m11 > m22 {
+            let y = (m11 - m00 - m22 + 1.0).sqrt() * 0.5;
+            let f = 0.25 / y;
+            Quat::new((m.c0.y + m.c1.x) * f, y, (m.c1.z + m.c2.y) * f, (m.c2.x - m.c0.z) * f)
+        } else {
+            let z = (m22 - m00 - m11 + 1.0).sqrt() * 0.5;
+            let f = 0.25 / z;
+            Quat::new((m.c2.x + m.c0.z) * f, (m.c1.z + m.c2.y) * f, z, (m.c0.y - m.c1.x) * f)
+        }
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        let x2 = self.x * self.x;
+        let y2 = self.y * self.y;
+        let z2 = self.z * self.z;
+        let xy = self.x * self.y;
+        let xz = self.x * self.z;
+        let yz = self.y * self.z;
+        let wx = self.w * self.x;
+        let wy = self.w * self.y;
+        let wz = self.w * self.z;
+
+        Mat3 {
+            c0: Vec3::new(1.0 - 2.0 * (y2 + z2), 2.0 * (xy + wz), 2.0 * (xz - wy)),
+            c1: Vec3::new(2.0 * (xy - wz), 1.0 - 2.0 * (x2 + z2), 2.0 * (yz + wx)),
+            c2: Vec3::new(2.0 * (xz + wy), 2.0 * (yz - wx), 1.0 - 2.0 * (x2 + y2)),
+        }
+    }
+
+    pub fn vec(&self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    pub fn dot(a: Quat, b: Quat) -> f32 {
+        a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w
+    }
+
+    pub fn norm(&self) -> f32 {
+        Quat::dot(*self, *self).sqrt()
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite() && self.w.is_finite()
+    }
+
+    pub fn normalize(&self) -> Quat {
+        (1.0 / self.norm()) * *self
+    }
+
+    pub fn conj(&self) -> Quat {
+        Quat::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn inv(&self) -> Quat {
+        (1.0 / Quat::dot(*self, *self)) * self.conj()
+    }
+
+    // Rotates `v` by a unit quaternion, i.e. q * v * q^-1 without building the full products.
+    pub fn rotate(&self, v: Vec3) -> Vec3 {
+        let b = self.vec();
+        let t = 2.0 * Vec3::cross(b, v);
+        v + self.w * t + Vec3::cross(b, t)
+    }
+
+    pub fn nlerp(a: Quat, b: Quat, t: f32) -> Quat {
+        let b = if Quat::dot(a, b) < 0.0 { -b } else { b };
+        ((1.0 - t) * a + t * b).normalize()
+    }
+
+    pub fn slerp(a: Quat, b: Quat, t: f32) -> Quat {
+        let mut cos = Quat::dot(a, b);
+        let mut b = b;
+        if cos < 0.0 {
+            cos = -cos;
+            b = -b;
+        }
+        // Nearly parallel quaternions make sin(theta) vanish, nlerp is accurate enough there.
+        if cos > 0.9995 {
+            return Quat::nlerp(a, b, t);
+        }
+        let theta = cos.acos();
+        let inv_sin = 1.0 / theta.sin();
+        let wa = ((1.0 - t) * theta).sin() * inv_sin;
+        let wb = (t * theta).sin() * inv_sin;
+        wa * a + wb * b
+    }
+}
+
+impl From<Mat3> for Quat {
+    fn from(m: Mat3) -> Quat {
+        Quat::from_mat3(m)
+    }
+}
+
+impl From<Quat> for Mat3 {
+    fn from(q: Quat) -> Mat3 {
+        q.to_mat3()
+    }
+}
+
+impl ops::Add for Quat {
+    type Output = Self;
+
+    fn add(self, b: Self) -> Self {
+        Quat::new(self.x + b.x, self.y + b.y, self.z + b.z, self.w + b.w)
+    }
+}
+
+impl ops::Sub for Quat {
+    type Output = Self;
+
+    fn sub(self, b: Self) -> Self {
+        Quat::new(self.x - b.x, self.y - b.y, self.z - b.z, self.w - b.w)
+    }
+}
+
+impl ops::Neg for Quat {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Quat::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl ops::Mul<Quat> for f32 {
+    type Output = Quat;
+
+    fn mul(self, q: Quat) -> Quat {
+        Quat::new(self * q.x, self * q.y, self * q.z, self * q.w)
+    }
+}
+
+impl ops::Mul<Quat> for Quat {
+    type Output = Self;
+
+    fn mul(self, q: Self) -> Self {
+        Quat::new(
+            self.w * q.x + self.x * q.w + self.y * q.z - self.z * q.y,
+            self.w * q.y - self.x * q.z + self.y * q.w + self.z * q.x,
+            self.w * q.z + self.x * q.y - self.y * q.x + self.z * q.w,
+            self.w * q.w - self.x * q.x - self.y * q.y - self.z * q.z,
+        )
+    }
+}
+
+impl ops::Mul<Vec3> for Quat {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        self.rotate(v)
+    }
+}
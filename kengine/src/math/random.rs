@@ -0,0 +1,178 @@
+use super::vec3::Vec3;
+use std::f32::consts::PI;
+
+// PCG32 (O'Neill, XSH RR variant). Small, fast and fully determined by its seed and stream, so anything
+// generated from it is reproducible across runs and platforms. Not suitable for cryptography.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+    inc: u64,
+}
+
+const PCG_MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+const POISSON_ATTEMPTS: usize = 30;
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng::with_stream(seed, 0)
+    }
+
+    // Generators with the same seed but different streams give independent sequences.
+    pub fn with_stream(seed: u64, stream: u64) -> Rng {
+        let mut rng = Rng { state: 0, inc: (stream << 1) | 1 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(PCG_MULTIPLIER).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+
+    // Uniform in [0, 1), using the top 24 bits so every value is exactly representable.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + self.next_f32() * (max - min)
+    }
+
+    // Uniform in [0, bound) without modulo bias.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        assert!(bound > 0, "Rng::below needs a non-zero bound");
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let r = self.next_u32();
+            if r >= threshold {
+                return r % bound;
+            }
+        }
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    pub fn on_unit_sphere(&mut self) -> Vec3 {
+        let z = 1.0 - 2.0 * self.next_f32();
+        let phi = 2.0 * PI * self.next_f32();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        Vec3::new(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    pub fn in_unit_sphere(&mut self) -> Vec3 {
+        let r = self.next_f32().cbrt();
+        r * self.on_unit_sphere()
+    }
+
+    // Uniform over the hemisphere around the unit vector `normal`.
+    pub fn on_hemisphere(&mut self, normal: Vec3) -> Vec3 {
+        let v = self.on_unit_sphere();
+        if Vec3::dot(v, normal) < 0.0 {
+            -v
+        } else {
+            v
+        }
+    }
+
+    // Density proportional to the cosine with `normal`, the distribution of diffuse bounces. Projects a
+    // uniform disk sample up onto the hemisphere (Malley's method).
+    pub fn cosine_hemisphere(&mut self, normal: Vec3) -> Vec3 {
+        let u = self.next_f32();
+        let phi = 2.0 * PI * self.next_f32();
+        let r = u.sqrt();
+        let t = normal.orthogonal();
+        let b = Vec3::cross(normal, t);
+        (r * phi.cos()) * t + (r * phi.sin()) * b + (1.0 - u).max(0.0).sqrt() * normal
+    }
+}
+
+// Bridson's Poisson-disk sampling: points in [0, width] x [0, height] on the z = 0 plane, no two closer
+// than `radius`, and no gap left where another point would fit.
+pub fn poisson_disk_2d(rng: &mut Rng, width: f32, height: f32, radius: f32) -> Vec<Vec3> {
+    poisson_disk(rng, Vec3::new(width, height, 0.0), radius, 2)
+}
+
+// The same in the box from the origin to `size`.
+pub fn poisson_disk_3d(rng: &mut Rng, size: Vec3, radius: f32) -> Vec<Vec3> {
+    poisson_disk(rng, size, radius, 3)
+}
+
+fn poisson_disk(rng: &mut Rng, size: Vec3, radius: f32, dims: usize) -> Vec<Vec3> {
+    assert!(radius > 0.0, "Poisson-disk radius must be positive");
+    // Cells are small enough to hold at most one point, so the grid stores a single index per cell.
+    let cell = radius / (dims as f32).sqrt();
+    let count = |extent: f32| ((extent / cell).ceil() as usize).max(1);
+    let n = [count(size.x), count(size.y), if dims == 2 { 1 } else { count(size.z) }];
+    let mut grid = vec![usize::MAX; n[0] * n[1] * n[2]];
+    let cell_of = |p: Vec3| {
+        let c = |v: f32, i: usize| ((v / cell) as usize).min(n[i] - 1);
+        [c(p.x, 0), c(p.y, 1), if dims == 2 { 0 } else { c(p.z, 2) }]
+    };
+    let index = |c: [usize; 3]| (c[2] * n[1] + c[1]) * n[0] + c[0];
+
+    let mut points = Vec::new();
+    let mut active = Vec::new();
+    let first = Vec3::new(rng.next_f32() * size.x, rng.next_f32() * size.y, if dims == 2 { 0.0 } else { rng.next_f32() * size.z });
+    grid[index(cell_of(first))] = 0;
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let slot = rng.below(active.len() as u32) as usize;
+        let centre = points[active[slot]];
+        let mut found = false;
+        for _ in 0..POISSON_ATTEMPTS {
+            // Uniform by area (or volume) over the shell between radius and 2 * radius.
+            let (dir, dist) = if dims == 2 {
+                let phi = 2.0 * PI * rng.next_f32();
+                (Vec3::new(phi.cos(), phi.sin(), 0.0), radius * (1.0 + 3.0 * rng.next_f32()).sqrt())
+            } else {
+                (rng.on_unit_sphere(), radius * (1.0 + 7.0 * rng.next_f32()).cbrt())
+            };
+            let p = centre + dist * dir;
+            let inside = |v: f32, extent: f32| v >= 0.0 && v <= extent;
+            if !inside(p.x, size.x) || !inside(p.y, size.y) || (dims == 3 && !inside(p.z, size.z)) {
+                continue;
+            }
+
+            let c = cell_of(p);
+            let near = |i: usize| c[i].saturating_sub(2)..(c[i] + 3).min(n[i]);
+            let clear = near(2).all(|z| {
+                near(1).all(|y| {
+                    near(0).all(|x| {
+                        let other = grid[index([x, y, z])];
+                        other == usize::MAX || (points[other] - p).length() >= radius
+                    })
+                })
+            });
+            if clear {
+                grid[index(c)] = points.len();
+                active.push(points.len());
+                points.push(p);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            active.swap_remove(slot);
+        }
+    }
+    points
+}
@@ -0,0 +1,73 @@
+use super::vec3::Vec3;
+use super::mat3::Mat3;
+use super::transform4d::Transform4D;
+use super::dvec3::DVec3;
+use super::dmat3::DMat3;
+use super::dtransform4d::DTransform4D;
+use super::mat4::Mat4;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Readable serde forms for the types whose storage is private. Types with public fields derive theirs.
+// The hand-editable TRS form of Transform4D lives in `trs`, the array forms in `compact`.
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Transform4D")]
+struct MatVec {
+    mat3: Mat3,
+    translation: Vec3,
+}
+
+impl Serialize for Transform4D {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MatVec { mat3: self.mat3(), translation: self.translation() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Transform4D {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Transform4D, D::Error> {
+        let MatVec { mat3, translation } = MatVec::deserialize(deserializer)?;
+        Ok(Transform4D::from_mat_vec(mat3, translation))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "DTransform4D")]
+struct DMatVec {
+    mat3: DMat3,
+    translation: DVec3,
+}
+
+impl Serialize for DTransform4D {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        DMatVec { mat3: self.mat3(), translation: self.translation() }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DTransform4D {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<DTransform4D, D::Error> {
+        let DMatVec { mat3, translation } = DMatVec::deserialize(deserializer)?;
+        Ok(DTransform4D::from_mat_vec(mat3, translation))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Mat4")]
+struct Cols {
+    c0: [f32; 4],
+    c1: [f32; 4],
+    c2: [f32; 4],
+    c3: [f32; 4],
+}
+
+impl Serialize for Mat4 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Cols { c0: self.col(0), c1: self.col(1), c2: self.col(2), c3: self.col(3) }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mat4 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Mat4, D::Error> {
+        let Cols { c0, c1, c2, c3 } = Cols::deserialize(deserializer)?;
+        Ok(Mat4::from_cols(c0, c1, c2, c3))
+    }
+}
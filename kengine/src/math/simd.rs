@@ -0,0 +1,119 @@
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+use super::mat3::Mat3;
+use super::transform4d::Transform4D;
+use std::arch::x86_64::*;
+
+// SSE2 versions of the hot math operations, used by the operators when the `simd` feature is on.
+// Only x86_64 is accelerated, there is no AVX or portable path and other targets keep the scalar code.
+// Products and sums are done in the same order as the scalar code, so the results are bit for bit identical.
+// Vec3 arithmetic stays scalar: packing three lanes costs more than the single operation saves.
+
+unsafe fn load3(v: Vec3) -> __m128 {
+    _mm_set_ps(0.0, v.z, v.y, v.x)
+}
+
+unsafe fn load4(a: &[f32; 4]) -> __m128 {
+    _mm_loadu_ps(a.as_ptr())
+}
+
+unsafe fn store(r: __m128) -> [f32; 4] {
+    let mut a = [0.0f32; 4];
+    _mm_storeu_ps(a.as_mut_ptr(), r);
+    a
+}
+
+unsafe fn store3(r: __m128) -> Vec3 {
+    let a = store(r);
+    Vec3::new(a[0], a[1], a[2])
+}
+
+// c0 * v.x + c1 * v.y + c2 * v.z
+unsafe fn combine3(c0: __m128, c1: __m128, c2: __m128, v: Vec3) -> __m128 {
+    let r = _mm_mul_ps(c0, _mm_set1_ps(v.x));
+    let r = _mm_add_ps(r, _mm_mul_ps(c1, _mm_set1_ps(v.y)));
+    _mm_add_ps(r, _mm_mul_ps(c2, _mm_set1_ps(v.z)))
+}
+
+pub fn vec4_add(a: Vec4, b: Vec4) -> Vec4 {
+    unsafe { store(_mm_add_ps(load4(a.as_ref()), load4(b.as_ref()))).into() }
+}
+
+pub fn vec4_sub(a: Vec4, b: Vec4) -> Vec4 {
+    unsafe { store(_mm_sub_ps(load4(a.as_ref()), load4(b.as_ref()))).into() }
+}
+
+pub fn vec4_scale(s: f32, v: Vec4) -> Vec4 {
+    unsafe { store(_mm_mul_ps(_mm_set1_ps(s), load4(v.as_ref()))).into() }
+}
+
+pub fn mat3_mul_vec3(m: &Mat3, v: Vec3) -> Vec3 {
+    unsafe { store3(combine3(load3(m.c0), load3(m.c1), load3(m.c2), v)) }
+}
+
+pub fn mat3_mul_mat3(a: &Mat3, b: &Mat3) -> Mat3 {
+    unsafe {
+        let (c0, c1, c2) = (load3(a.c0), load3(a.c1), load3(a.c2));
+        Mat3::from_cols(
+            store3(combine3(c0, c1, c2, b.c0)),
+            store3(combine3(c0, c1, c2, b.c1)),
+            store3(combine3(c0, c1, c2, b.c2)),
+        )
+    }
+}
+
+pub fn transform4d_mul_vec3(t: &Transform4D, v: Vec3) -> Vec3 {
+    let e = t.as_ref();
+    unsafe {
+        let r = combine3(load4(&e[0]), load4(&e[1]), load4(&e[2]), v);
+        store3(_mm_add_ps(r, load4(&e[3])))
+    }
+}
+
+pub fn transform4d_mul_vec4(t: &Transform4D, v: Vec4) -> Vec4 {
+    let e = t.as_ref();
+    unsafe {
+        let r = combine3(load4(&e[0]), load4(&e[1]), load4(&e[2]), v.truncate());
+        let r = _mm_add_ps(r, _mm_mul_ps(load4(&e[3]), _mm_set1_ps(v.w)));
+        store3(r).extend(v.w)
+    }
+}
+
+pub fn transform4d_mul_transform4d(a: &Transform4D, b: &Transform4D) -> Transform4D {
+    let e = a.as_ref();
+    unsafe {
+        let (c0, c1, c2, c3) = (load4(&e[0]), load4(&e[1]), load4(&e[2]), load4(&e[3]));
+        let m = b.mat3();
+        Transform4D::from_mat_vec(
+            Mat3::from_cols(
+                store3(combine3(c0, c1, c2, m.c0)),
+                store3(combine3(c0, c1, c2, m.c1)),
+                store3(combine3(c0, c1, c2, m.c2)),
+            ),
+            store3(_mm_add_ps(combine3(c0, c1, c2, b.translation()), c3)),
+        )
+    }
+}
+
+// The columns are loaded once for the whole slice.
+pub fn transform4d_transform_points(t: &Transform4D, points: &[Vec3], out: &mut [Vec3]) {
+    assert_eq!(points.len(), out.len());
+    let e = t.as_ref();
+    unsafe {
+        let (c0, c1, c2, c3) = (load4(&e[0]), load4(&e[1]), load4(&e[2]), load4(&e[3]));
+        for (p, o) in points.iter().zip(out.iter_mut()) {
+            *o = store3(_mm_add_ps(combine3(c0, c1, c2, *p), c3));
+        }
+    }
+}
+
+pub fn transform4d_transform_vectors(t: &Transform4D, vectors: &[Vec3], out: &mut [Vec3]) {
+    assert_eq!(vectors.len(), out.len());
+    let e = t.as_ref();
+    unsafe {
+        let (c0, c1, c2) = (load4(&e[0]), load4(&e[1]), load4(&e[2]));
+        for (v, o) in vectors.iter().zip(out.iter_mut()) {
+            *o = store3(combine3(c0, c1, c2, *v));
+        }
+    }
+}
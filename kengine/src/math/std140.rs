@@ -0,0 +1,63 @@
+use super::vec3::Vec3;
+use super::mat3::Mat3;
+
+// GLSL std140 rounds vec3 up to 16 bytes and stores mat3 as three vec4 columns, so the tightly packed
+// Vec3 and Mat3 cannot be written into uniform buffers as is. These types carry the required padding.
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Std140Vec3 {
+    pub v: Vec3,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _pad: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Std140Mat3 {
+    pub c0: Std140Vec3,
+    pub c1: Std140Vec3,
+    pub c2: Std140Vec3,
+}
+
+impl Std140Vec3 {
+    pub fn new(v: Vec3) -> Std140Vec3 {
+        Std140Vec3 { v, _pad: 0.0 }
+    }
+}
+
+impl Std140Mat3 {
+    pub fn new(m: Mat3) -> Std140Mat3 {
+        Std140Mat3 {
+            c0: Std140Vec3::new(m.c0),
+            c1: Std140Vec3::new(m.c1),
+            c2: Std140Vec3::new(m.c2),
+        }
+    }
+}
+
+impl From<Vec3> for Std140Vec3 {
+    fn from(v: Vec3) -> Std140Vec3 {
+        Std140Vec3::new(v)
+    }
+}
+
+impl From<Std140Vec3> for Vec3 {
+    fn from(v: Std140Vec3) -> Vec3 {
+        v.v
+    }
+}
+
+impl From<Mat3> for Std140Mat3 {
+    fn from(m: Mat3) -> Std140Mat3 {
+        Std140Mat3::new(m)
+    }
+}
+
+impl From<Std140Mat3> for Mat3 {
+    fn from(m: Std140Mat3) -> Mat3 {
+        Mat3::from_cols(m.c0.v, m.c1.v, m.c2.v)
+    }
+}
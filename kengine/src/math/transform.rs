@@ -0,0 +1,150 @@
+use super::vec3::Vec3;
+use super::mat3::Mat3;
+use super::quat::Quat;
+use super::transform4d::Transform4D;
+use std::ops;
+
+// Columns shorter than this fraction of the longest one count as zero when decomposing.
+const DEGENERATE_EPS: f32 = 1e-6;
+
+// Translation, rotation and scale stored separately so they can be edited and interpolated.
+// Applied to a point in scale, rotation, translation order.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Transform {
+        Transform { translation, rotation, scale }
+    }
+
+    pub fn identity() -> Transform {
+        Transform {
+            translation: Vec3::zero(),
+            rotation: Quat::identity(),
+            scale: Vec3::one(),
+        }
+    }
+
+    pub fn from_translation(translation: Vec3) -> Transform {
+        Transform { translation, ..Transform::identity() }
+    }
+
+    pub fn from_rotation(rotation: Quat) -> Transform {
+        Transform { rotation, ..Transform::identity() }
+    }
+
+    pub fn from_scale(scale: Vec3) -> Transform {
+        Transform { scale, ..Transform::identity() }
+    }
+
+    pub fn to_transform4d(&self) -> Transform4D {
+        let s = &self.scale;
+        Transform4D::from_mat_vec(self.rotation.to_mat3() * Mat3::scale(s.x, s.y, s.z), self.translation)
+    }
+
+    // Drops any shear present in `m`, see decompose.
+    pub fn from_transform4d(m: &Transform4D) -> Option<Transform> {
+        Transform::decompose(m).map(|(t, _)| t)
+    }
+
+    // Splits the upper 3x3 part of `m` into rotation * shear * scale, where shear is the unit upper
+    // triangular matrix with (xy, xz, yz) above the diagonal. A reflection is folded into a negative x scale.
+    // Singular matrices, with a zero column or one lying in the span of the columns before it, have no such
+    // form and give None.
+    pub fn decompose(m: &Transform4D) -> Option<(Transform, Vec3)> {
+        let m3 = m.mat3();
+        if !m3.is_finite() {
+            return None;
+        }
+        let tiny = DEGENERATE_EPS * m3.c0.length().max(m3.c1.length()).max(m3.c2.length());
+
+        let sx = m3.c0.length();
+        if sx <= tiny {
+            return None;
+        }
+        let r0 = (1.0 / sx) * m3.c0;
+
+        let xy = Vec3::dot(r0, m3.c1);
+        let c1 = m3.c1 - xy * r0;
+        let sy = c1.length();
+        if sy <= tiny {
+            return None;
+        }
+        let r1 = (1.0 / sy) * c1;
+
+        let xz = Vec3::dot(r0, m3.c2);
+        let yz = Vec3::dot(r1, m3.c2);
+        let c2 = m3.c2 - xz * r0 - yz * r1;
+        let sz = c2.length();
+        if sz <= tiny {
+            return None;
+        }
+        let r2 = (1.0 / sz) * c2;
+
+        // Flipping r0 also flips the sign of the shear terms measured along it.
+        let (sx, r0, xy, xz) = if Vec3::dot(Vec3::cross(r0, r1), r2) < 0.0 {
+            (-sx, -r0, -xy, -xz)
+        } else {
+            (sx, r0, xy, xz)
+        };
+        let transform = Transform {
+            translation: m.translation(),
+            rotation: Quat::from_mat3(Mat3::from_cols(r0, r1, r2)),
+            scale: Vec3::new(sx, sy, sz),
+        };
+        Some((transform, Vec3::new(xy / sy, xz / sz, yz / sz)))
+    }
+
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        self.rotation.rotate(mul_elem(self.scale, p)) + self.translation
+    }
+
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        self.rotation.rotate(mul_elem(self.scale, v))
+    }
+
+    // Exact for uniform scale. With non-uniform scale the true inverse has shear, which TRS cannot hold.
+    pub fn inv(&self) -> Transform {
+        let rotation = self.rotation.conj();
+        let scale = Vec3::new(1.0 / self.scale.x, 1.0 / self.scale.y, 1.0 / self.scale.z);
+        let translation = mul_elem(scale, rotation.rotate(-self.translation));
+        Transform { translation, rotation, scale }
+    }
+
+    pub fn lerp(a: Transform, b: Transform, t: f32) -> Transform {
+        Transform {
+            translation: Vec3::lerp(a.translation, b.translation, t),
+            rotation: Quat::slerp(a.rotation, b.rotation, t),
+            scale: Vec3::lerp(a.scale, b.scale, t),
+        }
+    }
+}
+
+fn mul_elem(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3::new(a.x * b.x, a.y * b.y, a.z * b.z)
+}
+
+impl From<Transform> for Transform4D {
+    fn from(t: Transform) -> Transform4D {
+        t.to_transform4d()
+    }
+}
+
+// Like inv, exact when `self` has uniform scale.
+impl ops::Mul<Transform> for Transform {
+    type Output = Transform;
+
+    fn mul(self, t: Transform) -> Transform {
+        Transform {
+            translation: self.transform_point(t.translation),
+            rotation: self.rotation * t.rotation,
+            scale: mul_elem(self.scale, t.scale),
+        }
+    }
+}
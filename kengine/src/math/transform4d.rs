@@ -1,9 +1,13 @@
 use super::mat3::Mat3;
 use super::vec3::Vec3;
+use super::vec4::Vec4;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use super::simd;
 use std::ops;
-use std::mem;
+use std::convert;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Transform4D {
     e: [[f32; 4]; 4],
 }
@@ -23,6 +27,7 @@ impl Transform4D {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         n00: f32, n01: f32, n02: f32, n03: f32,
         n10: f32, n11: f32, n12: f32, n13: f32,
@@ -38,11 +43,88 @@ impl Transform4D {
         }
     }
 
+    pub fn identity() -> Transform4D {
+        Transform4D::from_mat_vec(Mat3::identity(), Vec3::zero())
+    }
+
+    pub fn mat3(&self) -> Mat3 {
+        let xyz = |c: [f32; 4]| Vec3::new(c[0], c[1], c[2]);
+        Mat3::from_cols(xyz(self.e[0]), xyz(self.e[1]), xyz(self.e[2]))
+    }
+
+    pub fn translation(&self) -> Vec3 {
+        Vec3::new(self.e[3][0], self.e[3][1], self.e[3][2])
+    }
+
+    // Same as `self * p`: positions are translated.
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        *self * p
+    }
+
+    // Directions and offsets ignore the translation column.
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        self.mat3() * v
+    }
+
+    // Normals have to stay perpendicular to transformed surfaces, so they go through the inverse transpose
+    // of the upper 3x3 part. The result is not normalized.
+    pub fn transform_normal(&self, n: Vec3) -> Vec3 {
+        let inv = self.mat3().inv();
+        Vec3::new(Vec3::dot(inv.c0, n), Vec3::dot(inv.c1, n), Vec3::dot(inv.c2, n))
+    }
+
+    pub fn transform_points(&self, points: &[Vec3], out: &mut [Vec3]) {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::transform4d_transform_points(self, points, out)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            assert_eq!(points.len(), out.len());
+            for (p, o) in points.iter().zip(out.iter_mut()) {
+                *o = *self * *p;
+            }
+        }
+    }
+
+    pub fn transform_vectors(&self, vectors: &[Vec3], out: &mut [Vec3]) {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::transform4d_transform_vectors(self, vectors, out)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            assert_eq!(vectors.len(), out.len());
+            let m = self.mat3();
+            for (v, o) in vectors.iter().zip(out.iter_mut()) {
+                *o = m * *v;
+            }
+        }
+    }
+
+    pub fn det(&self) -> f32 {
+        self.mat3().det()
+    }
+
+    // None when the upper 3x3 part is near-singular, see Mat3::try_inv.
+    pub fn try_inv(&self) -> Option<Transform4D> {
+        if self.is_finite() && self.mat3().try_inv().is_some() {
+            Some(self.inv())
+        } else {
+            None
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.e.iter().flatten().all(|x| x.is_finite())
+    }
+
     pub fn inv(&self) -> Transform4D {
-        let a = *unsafe { &*(self.e[0].as_ptr() as *const Vec3) };
-        let b = *unsafe { &*(self.e[1].as_ptr() as *const Vec3) };
-        let c = *unsafe { &*(self.e[2].as_ptr() as *const Vec3) };
-        let d = *unsafe { &*(self.e[3].as_ptr() as *const Vec3) };
+        let xyz = |c: [f32; 4]| Vec3::new(c[0], c[1], c[2]);
+        let a = xyz(self.e[0]);
+        let b = xyz(self.e[1]);
+        let c = xyz(self.e[2]);
+        let d = xyz(self.e[3]);
 
         let s = Vec3::cross(a, b);
         let t = Vec3::cross(c, d);
@@ -64,15 +146,44 @@ impl Transform4D {
     }
 }
 
+// Treats `v` as a point. Use transform_vector or transform_normal for directions.
 impl ops::Mul<Vec3> for Transform4D {
     type Output = Vec3;
 
     fn mul(self, v: Vec3) -> Vec3 {
-        Vec3::new(
-            self.e[0][0] * v.x + self.e[1][0] * v.y + self.e[2][0] * v.z + self.e[3][0],
-            self.e[0][1] * v.x + self.e[1][1] * v.y + self.e[2][1] * v.z + self.e[3][1],
-            self.e[0][2] * v.x + self.e[1][2] * v.y + self.e[2][2] * v.z + self.e[3][2],
-        )
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::transform4d_mul_vec3(&self, v)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            Vec3::new(
+                self.e[0][0] * v.x + self.e[1][0] * v.y + self.e[2][0] * v.z + self.e[3][0],
+                self.e[0][1] * v.x + self.e[1][1] * v.y + self.e[2][1] * v.z + self.e[3][1],
+                self.e[0][2] * v.x + self.e[1][2] * v.y + self.e[2][2] * v.z + self.e[3][2],
+            )
+        }
+    }
+}
+
+// The translation is scaled by w, so points (w = 1) are translated and directions (w = 0) are not.
+impl ops::Mul<Vec4> for Transform4D {
+    type Output = Vec4;
+
+    fn mul(self, v: Vec4) -> Vec4 {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::transform4d_mul_vec4(&self, v)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            Vec4::new(
+                self.e[0][0] * v.x + self.e[1][0] * v.y + self.e[2][0] * v.z + self.e[3][0] * v.w,
+                self.e[0][1] * v.x + self.e[1][1] * v.y + self.e[2][1] * v.z + self.e[3][1] * v.w,
+                self.e[0][2] * v.x + self.e[1][2] * v.y + self.e[2][2] * v.z + self.e[3][2] * v.w,
+                v.w,
+            )
+        }
     }
 }
 
@@ -80,19 +191,32 @@ impl ops::Mul<Transform4D> for Transform4D {
     type Output = Transform4D;
 
     fn mul(self, t: Transform4D) -> Self::Output {
-        Transform4D::new(
-            self.e[0][0] * t.e[0][0] + self.e[1][0] * t.e[0][1] + self.e[2][0] * t.e[0][2],
-            self.e[0][0] * t.e[1][0] + self.e[1][0] * t.e[1][1] + self.e[2][0] * t.e[1][2],
-            self.e[0][0] * t.e[2][0] + self.e[2][0] * t.e[2][1] + self.e[2][0] * t.e[2][2],
-            self.e[0][0] * t.e[3][0] + self.e[1][0] * t.e[3][1] + self.e[2][0] * t.e[3][2] + self.e[3][0],
-            self.e[0][1] * t.e[0][0] + self.e[1][1] * t.e[0][1] + self.e[2][1] * t.e[0][2],
-            self.e[0][1] * t.e[1][0] + self.e[1][1] * t.e[1][1] + self.e[2][1] * t.e[1][2],
-            self.e[0][1] * t.e[2][0] + self.e[2][1] * t.e[2][1] + self.e[2][1] * t.e[2][2],
-            self.e[0][1] * t.e[3][0] + self.e[1][1] * t.e[3][1] + self.e[2][1] * t.e[3][2] + self.e[3][1],
-            self.e[0][2] * t.e[0][0] + self.e[1][2] * t.e[0][1] + self.e[2][2] * t.e[0][2],
-            self.e[0][2] * t.e[1][0] + self.e[1][2] * t.e[1][1] + self.e[2][2] * t.e[1][2],
-            self.e[0][2] * t.e[2][0] + self.e[2][2] * t.e[2][1] + self.e[2][2] * t.e[2][2],
-            self.e[0][2] * t.e[3][0] + self.e[1][2] * t.e[3][1] + self.e[2][2] * t.e[3][2] + self.e[3][2],
-        )
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::transform4d_mul_transform4d(&self, &t)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            Transform4D::new(
+                self.e[0][0] * t.e[0][0] + self.e[1][0] * t.e[0][1] + self.e[2][0] * t.e[0][2],
+                self.e[0][0] * t.e[1][0] + self.e[1][0] * t.e[1][1] + self.e[2][0] * t.e[1][2],
+                self.e[0][0] * t.e[2][0] + self.e[1][0] * t.e[2][1] + self.e[2][0] * t.e[2][2],
+                self.e[0][0] * t.e[3][0] + self.e[1][0] * t.e[3][1] + self.e[2][0] * t.e[3][2] + self.e[3][0],
+                self.e[0][1] * t.e[0][0] + self.e[1][1] * t.e[0][1] + self.e[2][1] * t.e[0][2],
+                self.e[0][1] * t.e[1][0] + self.e[1][1] * t.e[1][1] + self.e[2][1] * t.e[1][2],
+                self.e[0][1] * t.e[2][0] + self.e[1][1] * t.e[2][1] + self.e[2][1] * t.e[2][2],
+                self.e[0][1] * t.e[3][0] + self.e[1][1] * t.e[3][1] + self.e[2][1] * t.e[3][2] + self.e[3][1],
+                self.e[0][2] * t.e[0][0] + self.e[1][2] * t.e[0][1] + self.e[2][2] * t.e[0][2],
+                self.e[0][2] * t.e[1][0] + self.e[1][2] * t.e[1][1] + self.e[2][2] * t.e[1][2],
+                self.e[0][2] * t.e[2][0] + self.e[1][2] * t.e[2][1] + self.e[2][2] * t.e[2][2],
+                self.e[0][2] * t.e[3][0] + self.e[1][2] * t.e[3][1] + self.e[2][2] * t.e[3][2] + self.e[3][2],
+            )
+        }
+    }
+}
+
+impl convert::AsRef<[[f32; 4]; 4]> for Transform4D {
+    fn as_ref(&self) -> &[[f32; 4]; 4] {
+        &self.e
     }
 }
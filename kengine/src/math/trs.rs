@@ -0,0 +1,43 @@
+use super::vec3::Vec3;
+use super::mat3::Mat3;
+use super::quat::Quat;
+use super::transform::Transform;
+use super::transform4d::Transform4D;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Translation, rotation and scale form of Transform4D, plus shear when there is any, for files that are
+// edited by hand, used as
+//
+//     #[serde(with = "kengine::math::trs")]
+//     transform: Transform4D,
+//
+// The form goes through Transform::decompose, so it is only exact up to rounding and a singular transform
+// fails to serialize. The default form writes the matrix as is.
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Transform4D")]
+struct Trs {
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    #[serde(default = "Vec3::zero", skip_serializing_if = "is_zero")]
+    shear: Vec3,
+}
+
+fn is_zero(v: &Vec3) -> bool {
+    *v == Vec3::zero()
+}
+
+pub fn serialize<S: Serializer>(value: &Transform4D, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::Error;
+    let (t, shear) = Transform::decompose(value).ok_or_else(|| S::Error::custom("singular Transform4D has no TRS form"))?;
+    Trs { translation: t.translation, rotation: t.rotation, scale: t.scale, shear }.serialize(serializer)
+}
+
+// Rebuilds rotation * shear * scale, the inverse of Transform::decompose.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Transform4D, D::Error> {
+    let Trs { translation, rotation, scale, shear } = Trs::deserialize(deserializer)?;
+    let shear = Mat3::from_cols(Vec3::x_axis(), Vec3::new(shear.x, 1.0, 0.0), Vec3::new(shear.y, shear.z, 1.0));
+    let m = rotation.to_mat3() * shear * Mat3::scale(scale.x, scale.y, scale.z);
+    Ok(Transform4D::from_mat_vec(m, translation))
+}
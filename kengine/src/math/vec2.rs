@@ -0,0 +1,132 @@
+use super::vec3::Vec3;
+use std::ops;
+use std::convert;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub fn new(x: f32, y: f32) -> Vec2 {
+        Vec2 { x, y }
+    }
+
+    pub fn dot(a: Vec2, b: Vec2) -> f32 {
+        a.x * b.x + a.y * b.y
+    }
+
+    // z component of the 3D cross product, i.e. the signed area of the parallelogram spanned by a and b.
+    pub fn cross(a: Vec2, b: Vec2) -> f32 {
+        a.x * b.y - a.y * b.x
+    }
+
+    pub fn perp(&self) -> Vec2 {
+        Vec2 { x: -self.y, y: self.x }
+    }
+
+    pub fn length(&self) -> f32 {
+        Vec2::dot(*self, *self).sqrt()
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+
+    pub fn normalize(&self) -> Vec2 {
+        (1.0 / self.length()) * *self
+    }
+
+    pub fn lerp(a: Vec2, b: Vec2, t: f32) -> Vec2 {
+        a + t * (b - a)
+    }
+
+    pub fn min(a: Vec2, b: Vec2) -> Vec2 {
+        Vec2 { x: a.x.min(b.x), y: a.y.min(b.y) }
+    }
+
+    pub fn max(a: Vec2, b: Vec2) -> Vec2 {
+        Vec2 { x: a.x.max(b.x), y: a.y.max(b.y) }
+    }
+
+    pub fn extend(&self, z: f32) -> Vec3 {
+        Vec3 { x: self.x, y: self.y, z }
+    }
+
+    pub fn zero() -> Vec2 {
+        Vec2 { x: 0.0, y: 0.0, }
+    }
+
+    pub fn one() -> Vec2 {
+        Vec2 { x: 1.0, y: 1.0, }
+    }
+
+    pub fn x_axis() -> Vec2 {
+        Vec2 { x: 1.0, y: 0.0, }
+    }
+
+    pub fn y_axis() -> Vec2 {
+        Vec2 { x: 0.0, y: 1.0, }
+    }
+}
+
+impl ops::Add for Vec2 {
+    type Output = Self;
+
+    fn add(self, b: Self) -> Self {
+        Vec2 { x: self.x + b.x, y: self.y + b.y }
+    }
+}
+
+impl ops::Sub for Vec2 {
+    type Output = Self;
+
+    fn sub(self, b: Self) -> Self {
+        Vec2 { x: self.x - b.x, y: self.y - b.y }
+    }
+}
+
+impl ops::Mul<Vec2> for f32 {
+    type Output = Vec2;
+
+    fn mul(self, b: Vec2) -> Vec2 {
+        Vec2 { x: self * b.x, y: self * b.y }
+    }
+}
+
+impl ops::Index<usize> for Vec2 {
+    type Output = f32;
+
+    fn index(&self, i: usize) -> &f32 {
+        &self.as_ref()[i]
+    }
+}
+
+impl ops::Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl convert::AsRef<[f32; 2]> for Vec2 {
+    fn as_ref(&self) -> &[f32; 2] {
+        bytemuck::cast_ref(self)
+    }
+}
+
+impl From<[f32; 2]> for Vec2 {
+    fn from(a: [f32; 2]) -> Vec2 {
+        Vec2::new(a[0], a[1])
+    }
+}
+
+impl From<Vec2> for [f32; 2] {
+    fn from(v: Vec2) -> [f32; 2] {
+        [v.x, v.y]
+    }
+}
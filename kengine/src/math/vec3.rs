@@ -1,8 +1,11 @@
+use super::vec2::Vec2;
+use super::vec4::Vec4;
 use std::ops;
 use std::convert;
-use std::mem;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -34,6 +37,44 @@ impl Vec3 {
         a - Vec3::dot(a, b) / Vec3::dot(b, b) * b
     }
 
+    pub fn length(&self) -> f32 {
+        Vec3::dot(*self, *self).sqrt()
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
+    pub fn normalize(&self) -> Vec3 {
+        (1.0 / self.length()) * *self
+    }
+
+    // Some unit vector perpendicular to `self`, which is expected to be a unit vector.
+    pub fn orthogonal(&self) -> Vec3 {
+        let axis = if self.x.abs() < 0.6 { Vec3::x_axis() } else { Vec3::y_axis() };
+        Vec3::cross(*self, axis).normalize()
+    }
+
+    pub fn lerp(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+        a + t * (b - a)
+    }
+
+    pub fn min(a: Vec3, b: Vec3) -> Vec3 {
+        Vec3 { x: a.x.min(b.x), y: a.y.min(b.y), z: a.z.min(b.z) }
+    }
+
+    pub fn max(a: Vec3, b: Vec3) -> Vec3 {
+        Vec3 { x: a.x.max(b.x), y: a.y.max(b.y), z: a.z.max(b.z) }
+    }
+
+    pub fn extend(&self, w: f32) -> Vec4 {
+        Vec4 { x: self.x, y: self.y, z: self.z, w }
+    }
+
+    pub fn truncate(&self) -> Vec2 {
+        Vec2 { x: self.x, y: self.y }
+    }
+
     pub fn zero() -> Vec3 {
         Vec3 { x: 0.0, y: 0.0, z: 0.0, }
     }
@@ -53,7 +94,6 @@ impl Vec3 {
     pub fn z_axis() -> Vec3 {
         Vec3 { x: 0.0, y: 0.0, z: 1.0, }
     }
-
 }
 
 impl ops::Add for Vec3 {
@@ -98,6 +138,18 @@ impl ops::Neg for Vec3 {
 
 impl convert::AsRef<[f32; 3]> for Vec3 {
     fn as_ref(&self) -> &[f32; 3] {
-        unsafe { mem::transmute(self) }
+        bytemuck::cast_ref(self)
+    }
+}
+
+impl From<[f32; 3]> for Vec3 {
+    fn from(a: [f32; 3]) -> Vec3 {
+        Vec3::new(a[0], a[1], a[2])
+    }
+}
+
+impl From<Vec3> for [f32; 3] {
+    fn from(v: Vec3) -> [f32; 3] {
+        [v.x, v.y, v.z]
     }
 }
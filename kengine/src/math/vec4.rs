@@ -0,0 +1,171 @@
+use super::vec3::Vec3;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use super::simd;
+use std::ops;
+use std::convert;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Vec4 {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Vec4 {
+        Vec4 { x, y, z, w }
+    }
+
+    // Homogeneous point, affected by translation.
+    pub fn point(p: Vec3) -> Vec4 {
+        p.extend(1.0)
+    }
+
+    // Homogeneous direction, unaffected by translation.
+    pub fn dir(d: Vec3) -> Vec4 {
+        d.extend(0.0)
+    }
+
+    pub fn dot(a: Vec4, b: Vec4) -> f32 {
+        a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w
+    }
+
+    pub fn length(&self) -> f32 {
+        Vec4::dot(*self, *self).sqrt()
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite() && self.w.is_finite()
+    }
+
+    pub fn normalize(&self) -> Vec4 {
+        (1.0 / self.length()) * *self
+    }
+
+    pub fn lerp(a: Vec4, b: Vec4, t: f32) -> Vec4 {
+        a + t * (b - a)
+    }
+
+    pub fn min(a: Vec4, b: Vec4) -> Vec4 {
+        Vec4 { x: a.x.min(b.x), y: a.y.min(b.y), z: a.z.min(b.z), w: a.w.min(b.w) }
+    }
+
+    pub fn max(a: Vec4, b: Vec4) -> Vec4 {
+        Vec4 { x: a.x.max(b.x), y: a.y.max(b.y), z: a.z.max(b.z), w: a.w.max(b.w) }
+    }
+
+    pub fn truncate(&self) -> Vec3 {
+        Vec3 { x: self.x, y: self.y, z: self.z }
+    }
+
+    // Divides by w, turning a homogeneous point back into a 3D one.
+    pub fn project(&self) -> Vec3 {
+        (1.0 / self.w) * self.truncate()
+    }
+
+    pub fn zero() -> Vec4 {
+        Vec4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0, }
+    }
+
+    pub fn one() -> Vec4 {
+        Vec4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0, }
+    }
+
+    pub fn x_axis() -> Vec4 {
+        Vec4 { x: 1.0, y: 0.0, z: 0.0, w: 0.0, }
+    }
+
+    pub fn y_axis() -> Vec4 {
+        Vec4 { x: 0.0, y: 1.0, z: 0.0, w: 0.0, }
+    }
+
+    pub fn z_axis() -> Vec4 {
+        Vec4 { x: 0.0, y: 0.0, z: 1.0, w: 0.0, }
+    }
+
+    pub fn w_axis() -> Vec4 {
+        Vec4 { x: 0.0, y: 0.0, z: 0.0, w: 1.0, }
+    }
+}
+
+impl ops::Add for Vec4 {
+    type Output = Self;
+
+    fn add(self, b: Self) -> Self {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::vec4_add(self, b)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            Vec4 { x: self.x + b.x, y: self.y + b.y, z: self.z + b.z, w: self.w + b.w }
+        }
+    }
+}
+
+impl ops::Sub for Vec4 {
+    type Output = Self;
+
+    fn sub(self, b: Self) -> Self {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::vec4_sub(self, b)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            Vec4 { x: self.x - b.x, y: self.y - b.y, z: self.z - b.z, w: self.w - b.w }
+        }
+    }
+}
+
+impl ops::Mul<Vec4> for f32 {
+    type Output = Vec4;
+
+    fn mul(self, b: Vec4) -> Vec4 {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            simd::vec4_scale(self, b)
+        }
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        {
+            Vec4 { x: self * b.x, y: self * b.y, z: self * b.z, w: self * b.w }
+        }
+    }
+}
+
+impl ops::Index<usize> for Vec4 {
+    type Output = f32;
+
+    fn index(&self, i: usize) -> &f32 {
+        &self.as_ref()[i]
+    }
+}
+
+impl ops::Neg for Vec4 {
+    type Output = Vec4;
+
+    fn neg(self) -> Vec4 {
+        Vec4::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl convert::AsRef<[f32; 4]> for Vec4 {
+    fn as_ref(&self) -> &[f32; 4] {
+        bytemuck::cast_ref(self)
+    }
+}
+
+impl From<[f32; 4]> for Vec4 {
+    fn from(a: [f32; 4]) -> Vec4 {
+        Vec4::new(a[0], a[1], a[2], a[3])
+    }
+}
+
+impl From<Vec4> for [f32; 4] {
+    fn from(v: Vec4) -> [f32; 4] {
+        [v.x, v.y, v.z, v.w]
+    }
+}
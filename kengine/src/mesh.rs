@@ -0,0 +1,279 @@
+use std::ops::Range;
+use wgpu::util::DeviceExt;
+use crate::colour::LinearRgba;
+use crate::error::{Error, Result};
+use crate::math::{Vec2, Vec3, Vec4};
+
+// Vertex inputs a mesh can carry. Each has a fixed shader location whether or not other attributes are
+// present, so one shader works with any mesh that has at least the attributes it reads:
+//
+//     layout(location=0) in vec3 a_position;
+//     layout(location=1) in vec2 a_tex_coords;
+//     layout(location=2) in vec3 a_normal;
+//     layout(location=3) in vec4 a_tangent;
+//     layout(location=4) in vec4 a_colour;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    Position,
+    Uv,
+    Normal,
+    // xyz is the tangent, w is +1 or -1 giving the handedness of the bitangent.
+    Tangent,
+    Colour,
+}
+
+// A named run of the index buffer, drawn with its own material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submesh {
+    pub name: String,
+    pub indices: Range<u32>,
+    pub material: Option<usize>,
+}
+
+// CPU side geometry. Positions are required, every other attribute is either empty or has one entry per
+// position. Triangles are listed in `indices` and split into `submeshes`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub positions: Vec<Vec3>,
+    pub uvs: Vec<Vec2>,
+    pub normals: Vec<Vec3>,
+    pub tangents: Vec<Vec4>,
+    pub colours: Vec<LinearRgba>,
+    pub indices: Vec<u32>,
+    pub submeshes: Vec<Submesh>,
+}
+
+// Interleaved layout of a mesh's vertex buffer, attributes in shader location order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VertexLayout {
+    pub array_stride: wgpu::BufferAddress,
+    pub attributes: Vec<wgpu::VertexAttribute>,
+}
+
+// GPU side of a MeshData. Indices are stored as u16 whenever every vertex can be addressed with them.
+pub struct Mesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
+    pub layout: VertexLayout,
+    pub submeshes: Vec<Submesh>,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 5] = [Attribute::Position, Attribute::Uv, Attribute::Normal, Attribute::Tangent, Attribute::Colour];
+
+    pub fn location(self) -> u32 {
+        match self {
+            Attribute::Position => 0,
+            Attribute::Uv => 1,
+            Attribute::Normal => 2,
+            Attribute::Tangent => 3,
+            Attribute::Colour => 4,
+        }
+    }
+
+    pub fn format(self) -> wgpu::VertexFormat {
+        match self {
+            Attribute::Position | Attribute::Normal => wgpu::VertexFormat::Float3,
+            Attribute::Uv => wgpu::VertexFormat::Float2,
+            Attribute::Tangent | Attribute::Colour => wgpu::VertexFormat::Float4,
+        }
+    }
+
+    pub fn size(self) -> wgpu::BufferAddress {
+        self.format().size()
+    }
+}
+
+impl MeshData {
+    // A mesh with positions only, drawn as a single submesh.
+    pub fn new(positions: Vec<Vec3>, indices: Vec<u32>) -> MeshData {
+        let submeshes = vec![Submesh {
+            name: String::new(),
+            indices: 0..indices.len() as u32,
+            material: None,
+        }];
+        MeshData {
+            positions,
+            indices,
+            submeshes,
+            ..MeshData::default()
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn has(&self, attribute: Attribute) -> bool {
+        match attribute {
+            Attribute::Position => !self.positions.is_empty(),
+            Attribute::Uv => !self.uvs.is_empty(),
+            Attribute::Normal => !self.normals.is_empty(),
+            Attribute::Tangent => !self.tangents.is_empty(),
+            Attribute::Colour => !self.colours.is_empty(),
+        }
+    }
+
+    pub fn attributes(&self) -> Vec<Attribute> {
+        Attribute::ALL.iter().copied().filter(|&a| self.has(a)).collect()
+    }
+
+    pub fn layout(&self) -> VertexLayout {
+        let mut offset = 0;
+        let attributes = self
+            .attributes()
+            .into_iter()
+            .map(|a| {
+                let attribute = wgpu::VertexAttribute {
+                    offset,
+                    shader_location: a.location(),
+                    format: a.format(),
+                };
+                offset += a.size();
+                attribute
+            })
+            .collect();
+        VertexLayout { array_stride: offset, attributes }
+    }
+
+    // u16 indices cover up to 65535 vertices. 0xFFFF is left out because some backends always treat it as
+    // primitive restart, which would drop the triangles using that vertex.
+    pub fn index_format(&self) -> wgpu::IndexFormat {
+        if self.vertex_count() <= u16::MAX as usize {
+            wgpu::IndexFormat::Uint16
+        } else {
+            wgpu::IndexFormat::Uint32
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let error = |reason: String| Err(Error::InvalidMesh(reason));
+        let n = self.vertex_count();
+        if n == 0 {
+            return error("mesh has no positions".to_string());
+        }
+        let lengths = [
+            (Attribute::Uv, self.uvs.len()),
+            (Attribute::Normal, self.normals.len()),
+            (Attribute::Tangent, self.tangents.len()),
+            (Attribute::Colour, self.colours.len()),
+        ];
+        if let Some((attribute, len)) = lengths.iter().find(|&&(_, len)| len != 0 && len != n) {
+            return error(format!("{} {:?} values for {} positions", len, attribute, n));
+        }
+        if self.indices.len() % 3 != 0 {
+            return error(format!("{} indices do not make whole triangles", self.indices.len()));
+        }
+        if let Some(i) = self.indices.iter().find(|&&i| i as usize >= n) {
+            return error(format!("index {} is out of range for {} vertices", i, n));
+        }
+        if let Some(s) = self.submeshes.iter().find(|s| s.indices.start > s.indices.end || s.indices.end as usize > self.indices.len()) {
+            return error(format!("submesh {:?} covers indices {:?} of {}", s.name, s.indices, self.indices.len()));
+        }
+        Ok(())
+    }
+
+    // Vertices interleaved to match `layout`.
+    pub fn vertex_bytes(&self) -> Vec<u8> {
+        let attributes = self.attributes();
+        let mut bytes = Vec::with_capacity(self.vertex_count() * self.layout().array_stride as usize);
+        for i in 0..self.vertex_count() {
+            for attribute in &attributes {
+                bytes.extend_from_slice(match attribute {
+                    Attribute::Position => bytemuck::bytes_of(&self.positions[i]),
+                    Attribute::Uv => bytemuck::bytes_of(&self.uvs[i]),
+                    Attribute::Normal => bytemuck::bytes_of(&self.normals[i]),
+                    Attribute::Tangent => bytemuck::bytes_of(&self.tangents[i]),
+                    Attribute::Colour => bytemuck::bytes_of(&self.colours[i]),
+                });
+            }
+        }
+        bytes
+    }
+
+    // Indices in `index_format`, u16 ones padded to a multiple of 4 bytes as buffer writes require.
+    pub fn index_bytes(&self) -> Vec<u8> {
+        match self.index_format() {
+            wgpu::IndexFormat::Uint16 => {
+                let mut indices: Vec<u16> = self.indices.iter().map(|&i| i as u16).collect();
+                if indices.len() % 2 != 0 {
+                    indices.push(0);
+                }
+                bytemuck::cast_slice(&indices).to_vec()
+            }
+            wgpu::IndexFormat::Uint32 => bytemuck::cast_slice(&self.indices).to_vec(),
+        }
+    }
+
+    // Smooth normals: each vertex gets the sum of the normals of the triangles using it, weighted by their
+    // area. Vertices no triangle covers point along +z. Fails like `validate` on a malformed mesh.
+    pub fn compute_normals(&mut self) -> Result<()> {
+        self.validate()?;
+        let mut normals = vec![Vec3::zero(); self.vertex_count()];
+        for triangle in self.indices.chunks_exact(3) {
+            let (a, b, c) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+            let n = Vec3::cross(self.positions[b] - self.positions[a], self.positions[c] - self.positions[a]);
+            for &i in &[a, b, c] {
+                normals[i] = normals[i] + n;
+            }
+        }
+        self.normals = normals
+            .into_iter()
+            .map(|n| if n.length() > 0.0 { n.normalize() } else { Vec3::z_axis() })
+            .collect();
+        Ok(())
+    }
+}
+
+impl VertexLayout {
+    pub fn buffer_layout(&self) -> wgpu::VertexBufferLayout {
+        wgpu::VertexBufferLayout {
+            array_stride: self.array_stride,
+            step_mode: wgpu::InputStepMode::Vertex,
+            attributes: &self.attributes,
+        }
+    }
+}
+
+impl Mesh {
+    pub fn new(device: &wgpu::Device, data: &MeshData, label: &str) -> Result<Mesh> {
+        data.validate()?;
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Vertex Buffer", label)),
+            contents: &data.vertex_bytes(),
+            usage: wgpu::BufferUsage::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Index Buffer", label)),
+            contents: &data.index_bytes(),
+            usage: wgpu::BufferUsage::INDEX,
+        });
+
+        Ok(Mesh {
+            vertex_buffer,
+            index_buffer,
+            index_format: data.index_format(),
+            layout: data.layout(),
+            submeshes: data.submeshes.clone(),
+        })
+    }
+
+    // Binds the buffers, expects the pipeline and bind groups to be set already.
+    pub fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        self.bind(render_pass);
+        for submesh in &self.submeshes {
+            render_pass.draw_indexed(submesh.indices.clone(), 0, 0..1);
+        }
+    }
+
+    // For switching materials between submeshes: call `bind` once, then this per submesh.
+    pub fn draw_submesh<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, index: usize) {
+        render_pass.draw_indexed(self.submeshes[index].indices.clone(), 0, 0..1);
+    }
+}
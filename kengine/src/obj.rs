@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::colour::LinearRgba;
+use crate::error::{Error, Result};
+use crate::material::Material;
+use crate::math::{Vec2, Vec3};
+use crate::mesh::{MeshData, Submesh};
+
+// A Wavefront OBJ file as one mesh. Every run of faces sharing an object or group name and a material
+// becomes a submesh whose material indexes `materials`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model {
+    pub mesh: MeshData,
+    pub materials: Vec<Material>,
+}
+
+// Reads an OBJ file along with the MTL libraries it names, which are looked up next to it.
+pub fn load_obj(path: impl AsRef<Path>) -> Result<Model> {
+    let path = path.as_ref();
+    parse_obj(&read(path)?, path.parent().unwrap_or_else(|| Path::new(""))).map_err(|e| in_file(e, path))
+}
+
+// Texture paths are resolved against the directory the MTL file is in.
+pub fn load_mtl(path: impl AsRef<Path>) -> Result<Vec<Material>> {
+    let path = path.as_ref();
+    parse_mtl(&read(path)?, path.parent().unwrap_or_else(|| Path::new(""))).map_err(|e| in_file(e, path))
+}
+
+// Faces are triangulated by ear clipping, so concave polygons come out right as well. Corners sharing
+// position, texture coordinate and normal become one vertex. Texture coordinates are flipped to put the
+// origin at the top left like wgpu's, and normals missing from the file are computed from the faces.
+// Library and material names are single words as in the format. A missing MTL library or an unknown
+// material only logs a warning, the faces are kept without a material. A malformed library is an error.
+pub fn parse_obj(source: &str, dir: &Path) -> Result<Model> {
+    let mut obj = ObjBuilder::default();
+    let mut materials: Vec<Material> = Vec::new();
+
+    for (number, line) in logical_lines(source) {
+        let error = |reason: String| Error::ObjParse { path: None, line: number, reason };
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = words.collect();
+        match keyword {
+            "v" => {
+                let v = floats(&args, 3, 7).map_err(error)?;
+                obj.positions.push(Vec3::new(v[0], v[1], v[2]));
+                // Some exporters append a vertex colour, a lone fourth value is the unused w.
+                obj.colours.push(if v.len() >= 6 { Some(LinearRgba::rgb(v[3], v[4], v[5])) } else { None });
+            }
+            "vt" => {
+                let t = floats(&args, 1, 3).map_err(error)?;
+                obj.uvs.push(Vec2::new(t[0], 1.0 - t.get(1).copied().unwrap_or(0.0)));
+            }
+            "vn" => {
+                let n = floats(&args, 3, 3).map_err(error)?;
+                obj.normals.push(Vec3::new(n[0], n[1], n[2]));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error(format!("face has {} vertices, at least 3 are needed", args.len())));
+                }
+                let corners = args.iter().map(|c| obj.corner(c)).collect::<std::result::Result<Vec<u32>, String>>().map_err(error)?;
+                triangulate(&obj.mesh.positions, &corners, &mut obj.indices);
+            }
+            "o" | "g" => obj.start_submesh(args.join(" "), obj.material),
+            "usemtl" => {
+                let name = single_name(&args).map_err(error)?;
+                let material = materials.iter().position(|m| m.name == name);
+                if material.is_none() {
+                    log::warn!("OBJ line {}: unknown material {:?}", number, name);
+                }
+                obj.start_submesh(obj.name.clone(), material);
+            }
+            "mtllib" => {
+                for file in args {
+                    match load_mtl(dir.join(file)) {
+                        Ok(library) => materials.extend(library),
+                        Err(Error::Io { ref source, .. }) if source.kind() == std::io::ErrorKind::NotFound => {
+                            log::warn!("OBJ line {}: skipping missing material library {}", number, file)
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            // Smoothing groups, lines, points and free-form geometry are not supported.
+            _ => {}
+        }
+    }
+
+    Ok(Model {
+        mesh: obj.finish()?,
+        materials,
+    })
+}
+
+pub fn parse_mtl(source: &str, dir: &Path) -> Result<Vec<Material>> {
+    let mut materials: Vec<Material> = Vec::new();
+
+    for (number, line) in logical_lines(source) {
+        let error = |reason: String| Error::ObjParse { path: None, line: number, reason };
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = words.collect();
+        if keyword == "newmtl" {
+            materials.push(Material::new(single_name(&args).map_err(error)?));
+            continue;
+        }
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => return Err(error(format!("{} before any newmtl", keyword))),
+        };
+        let colour = |args: &[&str]| floats(args, 1, 3).map(|c| if c.len() == 3 { LinearRgba::rgb(c[0], c[1], c[2]) } else { LinearRgba::rgb(c[0], c[0], c[0]) });
+        match keyword {
+            "Ka" => material.ambient = colour(&args).map_err(error)?,
+            "Kd" => material.diffuse = LinearRgba { a: material.diffuse.a, ..colour(&args).map_err(error)? },
+            "Ks" => material.specular = colour(&args).map_err(error)?,
+            "Ke" => material.emissive = colour(&args).map_err(error)?,
+            "Ns" => material.shininess = floats(&args, 1, 1).map_err(error)?[0],
+            "d" => material.diffuse.a = floats(&args, 1, 1).map_err(error)?[0],
+            "Tr" => material.diffuse.a = 1.0 - floats(&args, 1, 1).map_err(error)?[0],
+            "map_Kd" => material.diffuse_texture = Some(texture_path(dir, &args).map_err(error)?),
+            "map_Ks" => material.specular_texture = Some(texture_path(dir, &args).map_err(error)?),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_texture = Some(texture_path(dir, &args).map_err(error)?),
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+// Vertex data as it is assembled, attributes that are optional per vertex are filled in by `finish`.
+#[derive(Default)]
+struct ObjBuilder {
+    positions: Vec<Vec3>,
+    colours: Vec<Option<LinearRgba>>,
+    uvs: Vec<Vec2>,
+    normals: Vec<Vec3>,
+    vertices: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+    mesh: MeshData,
+    vertex_uvs: Vec<Option<Vec2>>,
+    vertex_normals: Vec<Option<Vec3>>,
+    vertex_colours: Vec<Option<LinearRgba>>,
+    indices: Vec<u32>,
+    name: String,
+    material: Option<usize>,
+    start: u32,
+}
+
+impl ObjBuilder {
+    // Index of the vertex for a `v`, `v/vt`, `v//vn` or `v/vt/vn` face corner.
+    fn corner(&mut self, corner: &str) -> std::result::Result<u32, String> {
+        let mut parts = corner.split('/');
+        let v = resolve(parts.next(), self.positions.len(), corner)?.ok_or_else(|| format!("face corner {:?} has no position", corner))?;
+        let vt = resolve(parts.next(), self.uvs.len(), corner)?;
+        let vn = resolve(parts.next(), self.normals.len(), corner)?;
+        if let Some(&index) = self.vertices.get(&(v, vt, vn)) {
+            return Ok(index);
+        }
+        let index = self.mesh.positions.len() as u32;
+        self.mesh.positions.push(self.positions[v]);
+        self.vertex_colours.push(self.colours[v]);
+        self.vertex_uvs.push(vt.map(|i| self.uvs[i]));
+        self.vertex_normals.push(vn.map(|i| self.normals[i]));
+        self.vertices.insert((v, vt, vn), index);
+        Ok(index)
+    }
+
+    fn start_submesh(&mut self, name: String, material: Option<usize>) {
+        self.end_submesh();
+        self.name = name;
+        self.material = material;
+    }
+
+    fn end_submesh(&mut self) {
+        let end = self.indices.len() as u32;
+        if end > self.start {
+            self.mesh.submeshes.push(Submesh {
+                name: self.name.clone(),
+                indices: self.start..end,
+                material: self.material,
+            });
+        }
+        self.start = end;
+    }
+
+    fn finish(mut self) -> Result<MeshData> {
+        self.end_submesh();
+        self.mesh.indices = self.indices;
+        if self.vertex_uvs.iter().any(Option::is_some) {
+            self.mesh.uvs = self.vertex_uvs.iter().map(|t| t.unwrap_or_else(Vec2::zero)).collect();
+        }
+        if self.vertex_colours.iter().any(Option::is_some) {
+            self.mesh.colours = self.vertex_colours.iter().map(|c| c.unwrap_or_else(|| LinearRgba::rgb(1.0, 1.0, 1.0))).collect();
+        }
+        if self.vertex_normals.iter().all(Option::is_some) {
+            self.mesh.normals = self.vertex_normals.iter().flatten().copied().collect();
+        } else {
+            // Computed normals only fill in for corners the file gave none.
+            self.mesh.compute_normals()?;
+            for (normal, given) in self.mesh.normals.iter_mut().zip(&self.vertex_normals) {
+                if let Some(given) = given {
+                    *normal = *given;
+                }
+            }
+        }
+        Ok(self.mesh)
+    }
+}
+
+// Ear clipping in the plane of the polygon, which keeps the winding of the face. Clipping starts at the
+// second corner, so convex faces give the same fan as the file order. Degenerate or self-intersecting
+// faces run out of ears, what is left of them is fanned.
+fn triangulate(positions: &[Vec3], corners: &[u32], indices: &mut Vec<u32>) {
+    let position = |corner: u32| positions[corner as usize];
+    let mut normal = Vec3::zero();
+    for (i, &corner) in corners.iter().enumerate() {
+        normal = normal + Vec3::cross(position(corner), position(corners[(i + 1) % corners.len()]));
+    }
+    let mut remaining = corners.to_vec();
+    // Twice the signed area of the triangle projected along the face normal.
+    let area = |a: u32, b: u32, c: u32| Vec3::dot(Vec3::cross(position(b) - position(a), position(c) - position(a)), normal);
+    let mut i = 1;
+    let mut misses = 0;
+    while remaining.len() > 3 && misses < remaining.len() {
+        let n = remaining.len();
+        let (a, b, c) = (remaining[(i + n - 1) % n], remaining[i % n], remaining[(i + 1) % n]);
+        let is_ear = area(a, b, c) > 0.0
+            && remaining.iter().all(|&p| p == a || p == b || p == c || area(a, b, p) < 0.0 || area(b, c, p) < 0.0 || area(c, a, p) < 0.0);
+        if is_ear {
+            indices.extend_from_slice(&[a, b, c]);
+            remaining.remove(i % n);
+            i %= n - 1;
+            misses = 0;
+        } else {
+            i = (i + 1) % n;
+            misses += 1;
+        }
+    }
+    for i in 1..remaining.len() - 1 {
+        indices.extend_from_slice(&[remaining[0], remaining[i], remaining[i + 1]]);
+    }
+}
+
+// Joins lines ending in a backslash with the next one and strips comments. Numbers are the first line of
+// each joined line, counting from 1.
+fn logical_lines(source: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (i, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        let (number, mut joined) = pending.take().unwrap_or_else(|| (i + 1, String::new()));
+        match line.trim_end().strip_suffix('\\') {
+            Some(head) => {
+                joined.push_str(head);
+                joined.push(' ');
+                pending = Some((number, joined));
+            }
+            None => {
+                joined.push_str(line);
+                lines.push((number, joined));
+            }
+        }
+    }
+    lines.extend(pending);
+    lines
+}
+
+fn single_name<'a>(args: &[&'a str]) -> std::result::Result<&'a str, String> {
+    match args {
+        [name] => Ok(name),
+        _ => Err(format!("expected one name, found {} words", args.len())),
+    }
+}
+
+fn floats(args: &[&str], min: usize, max: usize) -> std::result::Result<Vec<f32>, String> {
+    if args.len() < min || args.len() > max {
+        return Err(format!("expected {} to {} numbers, found {}", min, max, args.len()));
+    }
+    args.iter().map(|a| a.parse::<f32>().map_err(|_| format!("{:?} is not a number", a))).collect()
+}
+
+// OBJ indices count from 1, negative ones count back from the latest element. An empty part means the
+// corner has no such attribute.
+fn resolve(part: Option<&str>, len: usize, corner: &str) -> std::result::Result<Option<usize>, String> {
+    let part = match part {
+        Some(part) if !part.is_empty() => part,
+        _ => return Ok(None),
+    };
+    let index: i64 = part.parse().map_err(|_| format!("face corner {:?} has a malformed index", corner))?;
+    let resolved = if index > 0 { index - 1 } else { len as i64 + index };
+    if index == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(format!("face corner {:?} refers to element {} of {}", corner, index, len));
+    }
+    Ok(Some(resolved as usize))
+}
+
+// Options such as `-bm 0.5` come before the file name, which is the last word. Windows exporters write
+// backslashes.
+fn texture_path(dir: &Path, args: &[&str]) -> std::result::Result<PathBuf, String> {
+    let file = args.last().ok_or_else(|| "texture statement without a file name".to_string())?;
+    Ok(dir.join(file.replace('\\', "/")))
+}
+
+// Parse errors are reported against the innermost file, a library keeps its own path.
+fn in_file(e: Error, path: &Path) -> Error {
+    match e {
+        Error::ObjParse { path: None, line, reason } => Error::ObjParse { path: Some(path.to_path_buf()), line, reason },
+        e => e,
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|source| Error::Io { path: path.to_path_buf(), source })
+}
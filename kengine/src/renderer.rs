@@ -0,0 +1,270 @@
+use winit::{
+    event::*,
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+    window::Window,
+};
+use std::time::Instant;
+use futures::executor::block_on;
+use crate::error::{Error, Result};
+use crate::camera::{Camera, CameraBinding};
+use crate::depth::{DepthConfig, DepthTexture};
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+const DEFAULT_FOVY: f32 = std::f32::consts::FRAC_PI_4;
+const DEFAULT_NEAR: f32 = 0.1;
+const DEFAULT_FAR: f32 = 100.0;
+
+// The GPU side shared by every render target: the wgpu instance, the adapter, the device and its queue,
+// plus the colour format and size of whatever is being drawn into. The camera's uniform is uploaded before
+// every frame and its aspect ratio follows the size of the target, as does the size of the depth texture.
+pub struct Context {
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub format: wgpu::TextureFormat,
+    pub size: winit::dpi::PhysicalSize<u32>,
+    pub camera: Camera,
+    pub camera_binding: CameraBinding,
+    pub depth: DepthTexture,
+}
+
+// Draws into a window: the context plus the surface and the swap chain presenting to it.
+pub struct Renderer {
+    pub context: Context,
+    pub surface: wgpu::Surface,
+    pub sc_desc: wgpu::SwapChainDescriptor,
+    pub swap_chain: wgpu::SwapChain,
+}
+
+// Callbacks driven by `run`. `init` is called once the renderer exists and may fail, `input` gets every window event
+// first and returns true when it consumed it, `device_event` gets raw device input such as mouse motion, `update`
+// runs before every frame with the seconds since the previous one and may move the camera, and `render` records
+// the frame's commands, which are submitted and presented after it returns.
+pub trait App: 'static {
+    fn init(&mut self, context: &mut Context) -> Result<()>;
+
+    fn input(&mut self, _context: &Context, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    fn device_event(&mut self, _context: &Context, _event: &DeviceEvent) {}
+
+    // Called after the context has taken the new size, for size dependent resources of the app.
+    fn resize(&mut self, _context: &mut Context, _size: winit::dpi::PhysicalSize<u32>) {}
+
+    fn update(&mut self, _context: &mut Context, _dt: f32) {}
+
+    fn render(&mut self, context: &Context, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView);
+}
+
+impl Context {
+    pub async fn new(
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
+        format: wgpu::TextureFormat,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> Result<Context> {
+        let (device, queue) = adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::default(),
+                label: None,
+            },
+            None,
+        ).await?;
+        let mut camera = Camera::perspective(DEFAULT_FOVY, 1.0, DEFAULT_NEAR, DEFAULT_FAR);
+        camera.resize(size.width, size.height);
+        let camera_binding = CameraBinding::new(&device, &camera);
+        let depth = DepthTexture::new(&device, DepthConfig::default(), size.width, size.height);
+
+        Ok(Context {
+            instance,
+            adapter,
+            device,
+            queue,
+            format,
+            size,
+            camera,
+            camera_binding,
+            depth,
+        })
+    }
+
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.size = new_size;
+        self.camera.resize(new_size.width, new_size.height);
+        if new_size.width > 0 && new_size.height > 0 {
+            self.depth.resize(&self.device, new_size.width, new_size.height);
+        }
+    }
+
+    // Replaces the depth texture and switches the camera to the matching projection. Pipelines take their
+    // depth state from the config, so this belongs in App::init before they are created.
+    pub fn set_depth_config(&mut self, config: DepthConfig) {
+        self.depth = DepthTexture::new(&self.device, config, self.size.width, self.size.height);
+        self.camera.reverse_z = config.is_reversed();
+    }
+
+    pub fn update_camera(&self) {
+        self.camera_binding.update(&self.queue, &self.camera);
+    }
+
+    // Checks the SPIR-V header up front, wgpu panics on a malformed one.
+    pub fn create_shader_module(&self, label: &str, spirv: &[u8]) -> Result<wgpu::ShaderModule> {
+        check_spirv(label, spirv)?;
+        Ok(self.device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::util::make_spirv(spirv),
+            flags: wgpu::ShaderFlags::VALIDATION,
+        }))
+    }
+}
+
+impl Renderer {
+    pub async fn new(window: &Window) -> Result<Renderer> {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        let surface = unsafe { instance.create_surface(window) };
+        let adapter = instance.request_adapter(
+            &wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+            },
+        ).await.ok_or(Error::NoAdapter)?;
+        let format = adapter.get_swap_chain_preferred_format(&surface);
+        let context = Context::new(instance, adapter, format, size).await?;
+        let sc_desc = wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+            format,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        let swap_chain = context.device.create_swap_chain(&surface, &sc_desc);
+
+        Ok(Renderer {
+            context,
+            surface,
+            sc_desc,
+            swap_chain,
+        })
+    }
+
+    pub fn resize<A: App>(&mut self, app: &mut A, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.context.resize(new_size);
+        self.recreate_swap_chain(new_size);
+        app.resize(&mut self.context, new_size);
+    }
+
+    // A minimised window has no area to present to, the swap chain is kept until it gets one again.
+    fn recreate_swap_chain(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        if size.width > 0 && size.height > 0 {
+            self.sc_desc.width = size.width;
+            self.sc_desc.height = size.height;
+            self.swap_chain = self.context.device.create_swap_chain(&self.surface, &self.sc_desc);
+        }
+    }
+
+    // Frames are skipped while the window is minimised or acquiring one times out. A lost or outdated swap
+    // chain is recreated once, only when that does not help the surface counts as lost.
+    pub fn render<A: App>(&mut self, app: &mut A) -> Result<()> {
+        let size = self.context.size;
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+        let frame = match self.swap_chain.get_current_frame() {
+            Ok(frame) => frame,
+            Err(wgpu::SwapChainError::Lost) | Err(wgpu::SwapChainError::Outdated) => {
+                self.recreate_swap_chain(size);
+                match self.swap_chain.get_current_frame() {
+                    Ok(frame) => frame,
+                    Err(wgpu::SwapChainError::Timeout) => return Ok(()),
+                    Err(wgpu::SwapChainError::OutOfMemory) => return Err(Error::OutOfMemory),
+                    Err(_) => return Err(Error::SurfaceLost),
+                }
+            }
+            Err(wgpu::SwapChainError::Timeout) => return Ok(()),
+            Err(wgpu::SwapChainError::OutOfMemory) => return Err(Error::OutOfMemory),
+        };
+        let mut encoder = self.context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder"),
+        });
+        self.context.update_camera();
+        app.render(&self.context, &mut encoder, &frame.output.view);
+        self.context.queue.submit(std::iter::once(encoder.finish()));
+
+        Ok(())
+    }
+}
+
+pub(crate) fn check_spirv(label: &str, spirv: &[u8]) -> Result<()> {
+    let error = |reason: &str| Error::ShaderCompile { label: label.to_string(), reason: reason.to_string() };
+    if spirv.is_empty() || spirv.len() % 4 != 0 {
+        return Err(error("SPIR-V size is not a non-zero multiple of 4"));
+    }
+    if u32::from_le_bytes([spirv[0], spirv[1], spirv[2], spirv[3]]) != SPIRV_MAGIC {
+        return Err(error("missing SPIR-V magic number"));
+    }
+    Ok(())
+}
+
+// Opens a window and runs `app` until the window is closed or Escape is pressed. Only returns when setting
+// up the window, the renderer or the app fails. The event loop never returns, so an error while running is
+// logged and ends the process with status 1.
+pub fn run<A: App>(mut app: A) -> Result<()> {
+    let _ = env_logger::try_init();
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new().build(&event_loop)?;
+    let mut renderer = block_on(Renderer::new(&window))?;
+    app.init(&mut renderer.context)?;
+    let mut last_frame = Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent {
+                ref event,
+                window_id,
+            } if window_id == window.id() => {
+                if !app.input(&renderer.context, event) {
+                    match event {
+                        WindowEvent::CloseRequested
+                        | WindowEvent::KeyboardInput {
+                            input: KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                ..
+                            },
+                            ..
+                        } => *control_flow = ControlFlow::Exit,
+                        WindowEvent::Resized(physical_size) => {
+                            renderer.resize(&mut app, *physical_size);
+                        }
+                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                            renderer.resize(&mut app, **new_inner_size);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::DeviceEvent { ref event, .. } => {
+                app.device_event(&renderer.context, event);
+            }
+            Event::RedrawRequested(_) => {
+                let now = Instant::now();
+                let dt = (now - last_frame).as_secs_f32();
+                last_frame = now;
+                app.update(&mut renderer.context, dt);
+                if let Err(e) = renderer.render(&mut app) {
+                    log::error!("{}", e.report());
+                    std::process::exit(1);
+                }
+            }
+            Event::MainEventsCleared => {
+                window.request_redraw();
+            }
+            _ => {}
+        }
+    });
+}
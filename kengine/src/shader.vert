@@ -7,8 +7,13 @@ layout(location=1) in vec2 a_tex_coords;
 // Changed
 layout(location=0) out vec2 v_tex_coords;
 
+layout(set = 1, binding = 0) uniform Camera {
+    mat4 u_view_proj;
+    vec4 u_position;
+};
+
 void main() {
     // Changed
     v_tex_coords = a_tex_coords;
-    gl_Position = vec4(a_position, 1.0);
+    gl_Position = u_view_proj * vec4(a_position, 1.0);
 }